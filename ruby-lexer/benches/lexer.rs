@@ -0,0 +1,85 @@
+//! Measures the lexer's throughput on Ruby sources dominated by each category of token, along
+//! with a mixed sample.
+//!
+//! To compare against another revision of the lexer, save its results as a baseline and then
+//! compare the current revision against it:
+//!
+//! ```text
+//! cargo bench --bench lexer -- --save-baseline before
+//! git checkout <revision>
+//! cargo bench --bench lexer -- --baseline before
+//! ```
+//!
+//! Revisions from before the lexer borrowed its source take a char iterator instead, so the
+//! benchmark is run there with `Lexer::new(source.chars())`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use ruby_lexer::Lexer;
+
+/// The approximate size of each benchmarked source, in bytes
+const SOURCE_LEN: usize = 64 * 1024;
+
+const IDENTIFIERS: &str = "foo = bar.baz(qux, @ivar, @@cvar, $gvar, Const::Nested)\n\
+                           if defined?(value) then value else other_value end\n";
+const STRINGS: &str = "'single quoted' \"double #{quoted} string\\n\" %w[a list of words]\n\
+                       :symbol :\"quoted symbol\" %q(percent string) /regexp (with)+ groups/i\n";
+const NUMBERS: &str = "1 23 456_789 0x1F 0b1010 0o17 1.5 2.5e-3 12345678901234567890 3r 2i\n";
+const OPERATORS: &str = "a += b ** c - d * e / f % g << h >> i & j | k ^ l <=> m == n != o\n\
+                         p && q || !r ? s : t; u ||= v; w &&= x; y[0] = z[1..-1]\n";
+const COMMENTS: &str = "# A comment describing the method that follows, as found in most code\n\
+                        # which is documented line by line, with a blank comment below\n#\n";
+const HEREDOCS: &str =
+    "text = <<~EOS\n  The body of a squiggly heredoc,\n    indented within it\n  EOS\n\
+                        raw = <<-'EOS'\n  Single-quoted #{text}\n  EOS\n";
+const MIXED: &str = "# frozen_string_literal: true\n\
+                     module Shapes\n\
+                     \x20 class Circle < Shape\n\
+                     \x20   attr_reader :radius\n\n\
+                     \x20   def initialize(radius = 1.0)\n\
+                     \x20     @radius = radius\n\
+                     \x20     raise ArgumentError, \"radius must be positive\" unless radius > 0\n\
+                     \x20   end\n\n\
+                     \x20   # Returns the area of the circle\n\
+                     \x20   def area\n\
+                     \x20     Math::PI * @radius ** 2\n\
+                     \x20   end\n\n\
+                     \x20   def to_s\n\
+                     \x20     \"#<Circle radius=#{@radius}>\"\n\
+                     \x20   end\n\
+                     \x20 end\n\
+                     end\n\n\
+                     shapes = [1, 2.5, 3].map { |r| Shapes::Circle.new(r) }\n\
+                     puts shapes.sum(&:area).round(2) if shapes.any?\n";
+
+/// Repeats the sample until the source is at least `SOURCE_LEN` bytes long
+fn source(sample: &str) -> String {
+    sample.repeat((SOURCE_LEN + sample.len() - 1) / sample.len())
+}
+
+fn lex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lex");
+    let samples = [
+        ("identifiers", IDENTIFIERS),
+        ("strings", STRINGS),
+        ("numbers", NUMBERS),
+        ("operators", OPERATORS),
+        ("comments", COMMENTS),
+        ("heredocs", HEREDOCS),
+        ("mixed", MIXED),
+    ];
+    for (name, sample) in samples.iter() {
+        let source = source(sample);
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                Lexer::new(black_box(source.as_str()))
+                    .map(|result| result.unwrap())
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lex);
+criterion_main!(benches);
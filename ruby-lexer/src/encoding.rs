@@ -0,0 +1,237 @@
+// This module defines the character encodings in which source files may be provided, along with
+// the detection of a source file's encoding from its BOM or magic comment.
+
+use crate::magic_comments::{parse_coding, parse_magic_comment};
+use encoding_rs::Encoding;
+use std::fmt;
+
+/// The character encoding of a source file
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SourceEncoding {
+    /// `UTF-8` (the default), which also covers `US-ASCII`
+    #[default]
+    Utf8,
+    /// `ISO-8859-1`, which maps each byte to the code point of the same value
+    Latin1,
+    /// `ASCII-8BIT` (or `BINARY`), whose bytes are mapped to chars as in `ISO-8859-1`
+    Binary,
+    /// Another ASCII-compatible encoding (e.g. `Shift_JIS` or `EUC-JP`)
+    Other(&'static Encoding),
+}
+
+impl fmt::Display for SourceEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl SourceEncoding {
+    /// Returns the encoding with the given name (as used by Ruby or the WHATWG Encoding Standard)
+    ///
+    /// Returns `None` for unknown encodings and encodings that aren't ASCII-compatible.
+    pub fn for_name(name: &str) -> Option<Self> {
+        let encoding = match name.to_ascii_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" | "us-ascii" | "ascii" => SourceEncoding::Utf8,
+            "iso-8859-1" | "iso8859-1" => SourceEncoding::Latin1,
+            "ascii-8bit" | "binary" => SourceEncoding::Binary,
+            "cp932" | "windows-31j" => SourceEncoding::Other(encoding_rs::SHIFT_JIS),
+            "eucjp-ms" | "euc-jp-ms" | "cp51932" => SourceEncoding::Other(encoding_rs::EUC_JP),
+            name => match Encoding::for_label(name.as_bytes()) {
+                Some(encoding) if encoding.is_ascii_compatible() => SourceEncoding::Other(encoding),
+                _ => return None,
+            },
+        };
+        Some(encoding)
+    }
+
+    /// Returns the name of the encoding
+    pub fn name(&self) -> &'static str {
+        match self {
+            SourceEncoding::Utf8 => "UTF-8",
+            SourceEncoding::Latin1 => "ISO-8859-1",
+            SourceEncoding::Binary => "ASCII-8BIT",
+            SourceEncoding::Other(encoding) => encoding.name(),
+        }
+    }
+
+    /// Encodes the text, such as the value of a string token, into the bytes it has in this
+    /// encoding
+    ///
+    /// Characters that can't be represented in the encoding are replaced with `?`.
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        match self {
+            SourceEncoding::Utf8 => text.as_bytes().to_vec(),
+            SourceEncoding::Latin1 | SourceEncoding::Binary => text
+                .chars()
+                .map(|c| if (c as u32) < 0x100 { c as u8 } else { b'?' })
+                .collect(),
+            SourceEncoding::Other(encoding) => {
+                let mut bytes = Vec::new();
+                for c in text.chars() {
+                    let mut buf = [0; 4];
+                    let (encoded, _, unmappable) = encoding.encode(c.encode_utf8(&mut buf));
+                    if unmappable {
+                        bytes.push(b'?');
+                    } else {
+                        bytes.extend_from_slice(&encoded);
+                    }
+                }
+                bytes
+            }
+        }
+    }
+
+    /// Decodes bytes in this encoding, replacing malformed sequences with `U+FFFD REPLACEMENT
+    /// CHARACTER`
+    pub fn decode_lossy(&self, bytes: &[u8]) -> String {
+        match self {
+            SourceEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            SourceEncoding::Latin1 | SourceEncoding::Binary => {
+                bytes.iter().map(|b| *b as char).collect()
+            }
+            SourceEncoding::Other(encoding) => {
+                encoding.decode_without_bom_handling(bytes).0.into_owned()
+            }
+        }
+    }
+
+    /// Decodes bytes in this encoding
+    ///
+    /// If a malformed sequence is found, the text decoded before it is returned as the error.
+    pub(crate) fn decode(&self, bytes: &[u8]) -> Result<String, String> {
+        match self {
+            SourceEncoding::Utf8 => match std::str::from_utf8(bytes) {
+                Ok(text) => Ok(text.to_owned()),
+                Err(e) => Err(String::from_utf8_lossy(&bytes[..e.valid_up_to()]).into_owned()),
+            },
+            SourceEncoding::Latin1 | SourceEncoding::Binary => Ok(self.decode_lossy(bytes)),
+            SourceEncoding::Other(encoding) => {
+                let mut decoder = encoding.new_decoder_without_bom_handling();
+                let len = decoder
+                    .max_utf8_buffer_length_without_replacement(bytes.len())
+                    .unwrap();
+                let mut text = String::with_capacity(len);
+                match decoder.decode_to_string_without_replacement(bytes, &mut text, true) {
+                    (encoding_rs::DecoderResult::InputEmpty, _) => Ok(text),
+                    _ => Err(text),
+                }
+            }
+        }
+    }
+}
+
+/// The original bytes of a source file decoded from an encoding other than UTF-8
+///
+/// Some encodings map several byte sequences to the same character (e.g. the NEC and IBM
+/// extensions of `Windows-31J`), so the bytes of string literals are copied from the source rather
+/// than encoded again.
+pub(crate) struct EncodedSource {
+    bytes: Vec<u8>,
+    /// The offsets in the decoded text and in the bytes following each non-ASCII character
+    offsets: Vec<(usize, usize)>,
+}
+
+impl EncodedSource {
+    /// Maps the decoded text onto the bytes it was decoded from
+    pub(crate) fn new(encoding: SourceEncoding, bytes: &[u8], text: &str) -> Self {
+        let mut offsets = Vec::new();
+        let (mut offset, mut pos) = (0, 0);
+        while offset < text.len() {
+            // ASCII characters are represented by the same byte in ASCII-compatible encodings
+            if text.as_bytes()[offset].is_ascii() {
+                offset += 1;
+                pos += 1;
+                continue;
+            }
+            // Find the shortest sequence of bytes that decodes to the upcoming text
+            let rest = &text[offset..];
+            let (len, decoded_len) = (1..=4)
+                .take_while(|len| pos + len <= bytes.len())
+                .find_map(|len| match encoding.decode(&bytes[pos..pos + len]) {
+                    Ok(decoded) if !decoded.is_empty() && rest.starts_with(&decoded) => {
+                        Some((len, decoded.len()))
+                    }
+                    _ => None,
+                })
+                .unwrap();
+            offset += decoded_len;
+            pos += len;
+            offsets.push((offset, pos));
+        }
+        EncodedSource {
+            bytes: bytes.to_vec(),
+            offsets,
+        }
+    }
+
+    /// Returns the bytes from which the decoded text between the given offsets was decoded
+    pub(crate) fn slice(&self, start: usize, end: usize) -> &[u8] {
+        &self.bytes[self.byte_offset(start)..self.byte_offset(end)]
+    }
+
+    /// Converts an offset in the decoded text into an offset in the bytes
+    fn byte_offset(&self, offset: usize) -> usize {
+        match self
+            .offsets
+            .binary_search_by_key(&offset, |&(offset, _)| offset)
+        {
+            Ok(index) => self.offsets[index].1,
+            Err(0) => offset,
+            Err(index) => {
+                let (prev_offset, prev_pos) = self.offsets[index - 1];
+                prev_pos + offset - prev_offset
+            }
+        }
+    }
+}
+
+/// Describes how the encoding of a source file was determined
+pub(crate) struct DetectedEncoding {
+    /// The name of the encoding given by the magic comment, if any
+    pub name: Option<String>,
+    /// The line holding the magic comment
+    pub line: usize,
+    /// The length of the byte order mark preceding the source
+    pub bom_len: usize,
+}
+
+/// Determines the encoding of a source file from its UTF-8 byte order mark, or otherwise from
+/// the magic comment on its first line (or its second line, after a shebang)
+pub(crate) fn detect_encoding(bytes: &[u8]) -> DetectedEncoding {
+    if bytes.starts_with(b"\xEF\xBB\xBF") {
+        return DetectedEncoding {
+            name: Some("UTF-8".to_owned()),
+            line: 1,
+            bom_len: 3,
+        };
+    }
+    let mut lines = bytes.split(|b| *b == b'\n');
+    let first = lines.next().unwrap_or(&[]);
+    let (line, text) = if first.starts_with(b"#!") {
+        (2, lines.next().unwrap_or(&[]))
+    } else {
+        (1, first)
+    };
+
+    // Magic comments are ASCII, so the line is decoded as if it were ISO-8859-1
+    let text = SourceEncoding::Latin1.decode_lossy(text);
+    let comment = text.trim_start();
+    let name = if let Some(comment) = comment.strip_prefix('#') {
+        let pairs = parse_magic_comment(comment);
+        if pairs.is_empty() {
+            parse_coding(comment)
+        } else {
+            pairs
+                .into_iter()
+                .find(|(name, _)| name == "coding" || name == "encoding")
+                .map(|(_, value)| value)
+        }
+    } else {
+        None
+    };
+    DetectedEncoding {
+        name,
+        line,
+        bom_len: 0,
+    }
+}
@@ -1,78 +1,194 @@
 #[cfg(test)]
 mod tests;
 
+mod blocks;
 mod core;
+mod heredocs;
 mod identifiers;
+mod incremental;
 mod lex_state;
+mod lossless;
 mod numbers;
+mod recovery;
+mod strings;
+mod symbols;
+mod versions;
+mod warnings;
 mod whitespace;
 
-use crate::plugins::NewlinesHandler;
+use crate::encoding::{detect_encoding, EncodedSource};
+use crate::magic_comments::{parse_coding, parse_magic_comment};
 use crate::*;
 
+pub use incremental::{Checkpoint, IncrementalLexer, TokenDelta};
+pub use lossless::LosslessLexer;
+
+use blocks::{Block, BlockEnd};
+use heredocs::LineIndent;
 use lex_state::LexState;
+use strings::{Embedded, StringTerm, StringType};
 
 use env_logger;
-//use std::collections::HashMap;
+use std::borrow::Cow;
 use std::collections::VecDeque;
 
-/// The number of characters held by the lexer's buffer
-pub const BUFFER_SIZE: usize = 12;
-
 /// Holds the lexer's current state
-pub struct Lexer<T: Iterator<Item = char>> {
-    input: T,
+///
+/// The lexer reads directly from the source, so any amount of lookahead is available and token
+/// values are borrowed from the source where possible.
+pub struct Lexer<'src> {
+    /// The source being lexed, whose next character is at the offset of `location`
+    source: Cow<'src, str>,
     nesting_level: usize,
-    chr: VecDeque<Option<char>>,
     location: Location,
-    //keywords: HashMap<String, Token>,
     prev_lex_state: LexState,
     lex_state: LexState,
+    /// Tracks whether the bodies of heredocs are being lexed
     parsing_heredoc: bool,
-    lex_strterm: bool,
+    /// Heredocs whose bodies will be lexed once the current line has been lexed
+    pending_heredocs: VecDeque<StringTerm>,
+    /// The indentation of the lines of the last emitted segment of a squiggly heredoc
+    heredoc_indents: Option<Vec<LineIndent>>,
+    /// Tokens that have already been lexed but not yet emitted
+    pending_tokens: VecDeque<SpannedToken<'src>>,
+    /// Stack of the string literals currently being lexed, innermost last
+    lex_strterm: Vec<StringTerm>,
     seen_whitespace: bool,
     /// Tracks whether the previous token was considered the start of a command
     prev_command_state: bool,
     /// Tracks whether the upcoming token may be considered the start of a command
     command_state: bool,
+    /// Stack of the conditions being lexed (mruby's `cond_stack`), innermost last
+    ///
+    /// `true` marks the condition of a `while`, `until` or `for` loop, which is closed by
+    /// `do` or the end of the statement. `false` marks an enclosing bracket or interpolation.
+    cond_stack: Vec<bool>,
+    /// Stack of the command arguments being lexed (mruby's `cmdarg_stack`), innermost last
+    ///
+    /// `true` marks the arguments of a command call without parentheses, which are closed by
+    /// a block's `do` or the end of the statement. `false` marks an enclosing bracket or
+    /// interpolation.
+    cmdarg_stack: Vec<bool>,
+    /// The nesting levels at which the bodies of the lambdas being lexed begin (mruby's
+    /// `lpar_beg`), innermost last
+    lambda_nesting_levels: Vec<usize>,
+    /// Whether the lambdas whose bodies have not begun yet declare their parameters, innermost
+    /// last
+    lambda_parameters: Vec<bool>,
+    /// The blocks being lexed, innermost last
+    blocks: Vec<Block>,
+    /// The number of open keyword constructs that are closed by `end` (e.g. `def` or `do`)
+    keyword_depth: usize,
+    /// Whether a local variable named `it` is assigned outside of any block
+    it_assigned: bool,
+    /// Whether lexical errors are recorded and skipped rather than returned
+    recover_errors: bool,
+    /// The lexical errors recorded while recovering from errors
+    errors: Vec<LexicalError>,
+    /// The verbosity of the warnings reported while lexing
+    warning_level: WarningLevel,
+    /// The warnings reported while lexing, unless they are passed to a callback
+    warnings: Vec<Warning>,
+    /// Receives the warnings reported while lexing, if set
+    warning_callback: Option<Box<dyn FnMut(Warning)>>,
+    /// The values set by the magic comments lexed so far
+    magic_comments: MagicComments,
+    /// The line on which the last token other than whitespace, a comment or a newline ended
+    last_token_line: Option<usize>,
+    /// Whether the first line of the input is a shebang (e.g. `#!/usr/bin/env ruby`)
+    shebang: bool,
+    /// The encoding of the source, in which string literals hold their bytes
+    encoding: SourceEncoding,
+    /// The original bytes of a source decoded from an encoding other than UTF-8
+    encoded_source: Option<EncodedSource>,
+    /// The version of Ruby whose syntax is lexed
+    ruby_version: RubyVersion,
+    /// The nesting level of the method definition whose name or parameters are being lexed
+    method_definition: Option<usize>,
+    /// Whether a `=>` at the top level of the current statement belongs to a rescue clause, a
+    /// pattern or the arguments of a keyword such as `return`
+    arrow_allowed: bool,
 }
 
-impl<T> Lexer<T>
-where
-    T: Iterator<Item = char>,
-{
-    /// Initializes a lexer and pre-reads the buffered number of characters
-    pub fn new(input: T) -> Self {
+impl<'src> Lexer<'src> {
+    /// Initializes a lexer for the given source
+    ///
+    /// Both `\n` and `\r\n` line endings are recognized.
+    pub fn new(source: &'src str) -> Self {
+        Self::with_source(Cow::Borrowed(source))
+    }
+
+    fn with_source(source: Cow<'src, str>) -> Self {
         let _ = env_logger::builder().is_test(true).try_init();
         let mut lxr = Lexer {
-            input: input,
+            source,
             nesting_level: 0,
-            chr: VecDeque::with_capacity(BUFFER_SIZE),
             location: Location::new(0, 0),
-            //keywords: get_keywords(),
             prev_lex_state: LexState::EXPR_BEG,
             lex_state: LexState::EXPR_BEG,
             parsing_heredoc: false,
-            lex_strterm: false,
+            pending_heredocs: VecDeque::new(),
+            heredoc_indents: None,
+            pending_tokens: VecDeque::new(),
+            lex_strterm: Vec::new(),
             seen_whitespace: false,
             prev_command_state: false,
-            command_state: false,
+            command_state: true,
+            cond_stack: Vec::new(),
+            cmdarg_stack: Vec::new(),
+            lambda_nesting_levels: Vec::new(),
+            lambda_parameters: Vec::new(),
+            blocks: Vec::new(),
+            keyword_depth: 0,
+            it_assigned: false,
+            recover_errors: false,
+            errors: Vec::new(),
+            warning_level: WarningLevel::default(),
+            warnings: Vec::new(),
+            warning_callback: None,
+            magic_comments: MagicComments::default(),
+            last_token_line: None,
+            shebang: false,
+            encoding: SourceEncoding::default(),
+            encoded_source: None,
+            ruby_version: RubyVersion::default(),
+            method_definition: None,
+            arrow_allowed: false,
         };
-        // Preload the lexer's buffer
-        for _ in 1..=BUFFER_SIZE {
-            lxr.chr.push_back(lxr.input.next());
-        }
         lxr.location.reset(); // Moves to line 1, col 1
         lxr
     }
 
     /// This function takes a look at the next character, if any, and emits the relevant token
-    fn produce_token(&mut self) -> LexResult {
+    fn produce_token(&mut self) -> LexResult<'src> {
         self.prev_command_state = self.command_state;
         self.command_state = false;
-        while let Some(c) = self.char(0) {
+
+        // Lex the bodies of any heredocs started on the previous line
+        if self.parsing_heredoc && !self.lex_strterm.iter().any(|t| t.heredoc.is_some()) {
+            match self.lex_heredoc_body() {
+                Some(result) => return result,
+                None => self.parsing_heredoc = false,
+            }
+        }
+
+        // Continue lexing the contents of a string literal
+        if let Some(strterm) = self.lex_strterm.last_mut() {
+            match strterm.embedded {
+                None => {
+                    let tok_start = self.get_pos();
+                    return self.lex_string_content(tok_start);
+                }
+                Some(Embedded::Variable) => {
+                    // The embedded variable is lexed as normal, after which the string resumes
+                    strterm.embedded = None;
+                }
+                Some(Embedded::Expression { .. }) => {}
+            }
+        }
+
+        if let Some(c) = self.char(0) {
             self.prev_lex_state = self.lex_state;
-            // TODO: check if we're in a string first
             // TODO: parse.y:4573
             // TODO: parse.y:4586
 
@@ -87,77 +203,86 @@ where
                     return self.lex_single_line_comment();
                 }
                 '\n' => {
-                    // TODO: parse.y:4606
-                    match self.lex_state {
+                    // parse.y:4606
+                    if !self.pending_heredocs.is_empty() {
+                        // The bodies of any heredocs started on this line follow the newline
+                        self.parsing_heredoc = true;
+                    }
+                    match self.state() {
                         LexState::EXPR_BEG | LexState::EXPR_FNAME | LexState::EXPR_DOT => {
-                            if !self.parsing_heredoc && self.lex_strterm {
-                                // self.parse_string();
-                                todo!();
-                                break;
-                            }
                             // newline is not significant here
                             return self.emit_from_chars(Token::Newline, 1);
                         }
-                        LexState::EXPR_CLASS | LexState::EXPR_VALUE => {
-                            if !self.parsing_heredoc && self.lex_strterm {
-                                // self.parse_string();
-                                todo!();
-                                break;
-                            }
-                        }
                         _ => {}
                     }
-                    if !self.parsing_heredoc {
-                        // newline is significant
-                        return self.emit_from_chars(Token::LineTerminator, 1);
+                    // newline is not significant between a label and its value within brackets
+                    if self.is_labeled() && self.nesting_level > 0 {
+                        return self.emit_from_chars(Token::Newline, 1);
+                    }
+                    // newline is not significant before a leading `.` or `&.`, unless the
+                    // bodies of heredocs follow it
+                    if self.pending_heredocs.is_empty() && self.is_method_chain_continued() {
+                        return self.emit_from_chars(Token::Newline, 1);
                     }
-                    // TODO: parse.y:4754
-                    todo!()
+                    // newline is significant
+                    self.end_statement();
+                    self.method_definition = None;
+                    self.arrow_allowed = false;
+                    self.lex_state = LexState::EXPR_BEG;
+                    self.command_state = true;
+                    return self.emit_from_chars(Token::LineTerminator, 1);
                 }
                 '*' => {
                     // parse.y:4652
-                    self.set_lexer_newline_state();
                     // **=
-                    if self.chars(3) == Some("**=".to_owned()) {
+                    if self.lookahead("**=") {
+                        self.set_lexer_newline_state();
                         return self.emit_from_chars(
                             Token::AssignmentOperator {
-                                value: "**=".to_owned(),
+                                value: "**=".into(),
                             },
                             3,
                         );
                     }
-                    // **
-                    else if self.char(1) == Some('*') {
-                        if self.is_spcarg(c) {
-                            self.warn("'**' interpreted as argument prefix");
-                            return self.emit_from_chars(Token::TwoStar, 2);
-                        } else if self.is_beg() {
-                            return self.emit_from_chars(Token::TwoStar, 2);
-                        } else {
-                            return self.emit_from_chars(Token::OpExponent, 2);
-                        }
-                    }
                     // *=
-                    else if self.char(1) == Some('=') {
-                        return self.emit_from_chars(
-                            Token::AssignmentOperator {
-                                value: "*=".to_owned(),
-                            },
-                            2,
-                        );
+                    if self.char(1) == Some('=') {
+                        self.set_lexer_newline_state();
+                        return self
+                            .emit_from_chars(Token::AssignmentOperator { value: "*=".into() }, 2);
                     }
-                    // *
-                    else if self.is_spcarg(c) {
-                        self.warn("'*' interpreted as argument prefix");
-                        return self.emit_from_chars(Token::Star, 1);
-                    } else if self.is_beg() {
-                        return self.emit_from_chars(Token::Star, 1);
-                    } else {
-                        return self.emit_from_chars(Token::OpMultiply, 1);
+                    // ** or *
+                    let len = if self.char(1) == Some('*') { 2 } else { 1 };
+                    let prefix = self.char(len).is_some_and(|c| self.is_spcarg(c));
+                    let token = match (len, prefix) {
+                        (2, true) => {
+                            self.warn(
+                                WarningKind::ArgumentPrefix,
+                                "'**' interpreted as argument prefix",
+                            );
+                            Token::TwoStar
+                        }
+                        (_, true) => {
+                            self.warn(
+                                WarningKind::ArgumentPrefix,
+                                "'*' interpreted as argument prefix",
+                            );
+                            Token::Star
+                        }
+                        (2, _) if self.is_beg() => Token::TwoStar,
+                        (_, _) if self.is_beg() => Token::Star,
+                        (2, _) => Token::OpExponent,
+                        (_, _) => Token::OpMultiply,
                     };
+                    self.set_lexer_newline_state();
+                    return self.emit_from_chars(token, len);
                 }
                 '!' => {
                     // parse.y:4697
+                    // !@
+                    if self.is_after_operator() && self.char(1) == Some('@') {
+                        self.lex_state = LexState::EXPR_ARG;
+                        return self.emit_from_chars(Token::OpNot, 2);
+                    }
                     self.set_lexer_newline_state();
                     // !=
                     if self.char(1) == Some('=') {
@@ -172,10 +297,14 @@ where
                 }
                 '=' => {
                     //  parse.y:4717
-                    // TODO: parse.y:4718 (multi-line comments)
+                    // parse.y:4718
+                    if self.is_multi_line_comment_start() {
+                        return self.lex_multi_line_comment();
+                    }
+                    let state = self.lex_state;
                     self.set_lexer_newline_state();
                     // ===
-                    if self.chars(3) == Some("===".to_owned()) {
+                    if self.lookahead("===") {
                         return self.emit_from_chars(Token::OpTripleEqual, 3);
                     }
                     // ==
@@ -188,14 +317,69 @@ where
                     }
                     // =>
                     else if self.char(1) == Some('>') {
+                        if self.is_rightward_assignment() {
+                            let location = self.get_pos();
+                            self.require_version(
+                                RubyVersion::Ruby3_0,
+                                "rightward assignment",
+                                location,
+                            )?;
+                        }
                         return self.emit_from_chars(Token::Arrow, 2);
                     }
                     // =
+                    if self.is_endless_method_body(state) {
+                        let location = self.get_pos();
+                        self.require_version(
+                            RubyVersion::Ruby3_0,
+                            "endless method definition",
+                            location,
+                        )?;
+                        self.method_definition = None;
+                        // An endless method definition isn't closed by `end`
+                        self.keyword_depth = self.keyword_depth.saturating_sub(1);
+                    }
                     return self.emit_from_chars(Token::OpAssign, 1);
                 }
                 '<' => {
                     // parse.y:4760
-                    todo!()
+                    if self.char(1) == Some('<')
+                        && self.state() != LexState::EXPR_DOT
+                        && self.state() != LexState::EXPR_CLASS
+                        && !self.is_end()
+                        && (!self.is_arg() || self.seen_whitespace)
+                    {
+                        if let Some(result) = self.lex_heredoc_identifier() {
+                            return result;
+                        }
+                    }
+                    if self.state() == LexState::EXPR_CLASS {
+                        self.command_state = true;
+                    }
+                    self.set_lexer_newline_state();
+                    if self.char(1) == Some('=') {
+                        // <=>
+                        if self.char(2) == Some('>') {
+                            return self.emit_from_chars(Token::OpCompare, 3);
+                        }
+                        // <=
+                        return self.emit_from_chars(Token::OpLtEqual, 2);
+                    }
+                    if self.char(1) == Some('<') {
+                        // <<=
+                        if self.char(2) == Some('=') {
+                            return self.emit_from_chars(
+                                Token::AssignmentOperator {
+                                    value: "<<=".into(),
+                                },
+                                3,
+                            );
+                        }
+                        // <<
+                        return self.emit_from_chars(Token::OpLeftShift, 2);
+                    }
+                    // <
+                    return self.emit_from_chars(Token::OpLt, 1);
                 }
                 '>' => {
                     // parse.y:4799
@@ -209,7 +393,7 @@ where
                         if self.char(2) == Some('=') {
                             return self.emit_from_chars(
                                 Token::AssignmentOperator {
-                                    value: ">>=".to_owned(),
+                                    value: ">>=".into(),
                                 },
                                 3,
                             );
@@ -221,47 +405,183 @@ where
                 }
                 '"' => {
                     // parse.y:4821
-                    todo!()
+                    return self.lex_quoted_string(StringType::DQUOTE);
                 }
                 '\'' => {
                     // parse.y:4825
-                    todo!()
+                    return self.lex_quoted_string(StringType::SQUOTE);
                 }
                 '`' => {
                     // parse.y:4829
-                    todo!()
+                    if self.state() == LexState::EXPR_FNAME {
+                        // Method name for backtick strings
+                        self.lex_state = LexState::EXPR_ENDFN;
+                        return self.emit_from_chars(Token::Backtick, 1);
+                    }
+                    if self.state() == LexState::EXPR_DOT {
+                        self.lex_state = if self.prev_command_state {
+                            LexState::EXPR_CMDARG
+                        } else {
+                            LexState::EXPR_ARG
+                        };
+                        return self.emit_from_chars(Token::Backtick, 1);
+                    }
+                    return self.lex_string(StringType::XQUOTE);
                 }
                 '?' => {
                     // parse.y:4844
-                    todo!()
+                    return self.lex_character_literal();
                 }
                 '&' => {
                     // parse.y:4912
-                    todo!()
+                    if self.char(1) == Some('&') {
+                        self.lex_state = LexState::EXPR_BEG;
+                        // &&=
+                        if self.char(2) == Some('=') {
+                            return self.emit_from_chars(
+                                Token::AssignmentOperator {
+                                    value: "&&=".into(),
+                                },
+                                3,
+                            );
+                        }
+                        // &&
+                        return self.emit_from_chars(Token::OpAnd, 2);
+                    }
+                    // &.
+                    if self.char(1) == Some('.') {
+                        self.lex_state = LexState::EXPR_DOT;
+                        return self.emit_from_chars(Token::SafeNavigation, 2);
+                    }
+                    // &=
+                    if self.char(1) == Some('=') {
+                        return self
+                            .emit_from_chars(Token::AssignmentOperator { value: "&=".into() }, 2);
+                    }
+                    // &
+                    let token = match self.char(1) {
+                        Some(c) if self.is_spcarg(c) => {
+                            self.warn(
+                                WarningKind::ArgumentPrefix,
+                                "'&' interpreted as argument prefix",
+                            );
+                            Token::Ampersand
+                        }
+                        _ if self.is_beg() => Token::Ampersand,
+                        _ => Token::OpBinAnd,
+                    };
+                    self.set_lexer_newline_state();
+                    return self.emit_from_chars(token, 1);
                 }
                 '|' => {
                     // parse.y:4951
-                    todo!()
+                    if self.char(1) == Some('|') && self.state() != LexState::EXPR_BEG {
+                        self.lex_state = LexState::EXPR_BEG;
+                        // ||=
+                        if self.char(2) == Some('=') {
+                            return self.emit_from_chars(
+                                Token::AssignmentOperator {
+                                    value: "||=".into(),
+                                },
+                                3,
+                            );
+                        }
+                        // ||
+                        return self.emit_from_chars(Token::OpOr, 2);
+                    }
+                    // |=
+                    if self.char(1) == Some('=') {
+                        return self
+                            .emit_from_chars(Token::AssignmentOperator { value: "|=".into() }, 2);
+                    }
+                    // | (including each bar of an empty block parameter list)
+                    self.set_lexer_newline_state();
+                    if self.state() == LexState::EXPR_BEG {
+                        // A block's parameters may begin with a keyword parameter
+                        self.lex_state |= LexState::EXPR_LABEL;
+                    }
+                    return self.emit_from_chars(Token::OpBinOr, 1);
                 }
                 '+' => {
-                    // TODO: parse.y:4976
-                    if Self::is_digit(self.char(1), 10) {
-                        return self.lex_number();
+                    // parse.y:4976
+                    if self.is_after_operator() {
+                        self.lex_state = LexState::EXPR_ARG;
+                        // +@
+                        if self.char(1) == Some('@') {
+                            return self.emit_from_chars(Token::OpUnaryPlus, 2);
+                        }
+                        return self.emit_from_chars(Token::OpPlus, 1);
+                    }
+                    // +=
+                    if self.char(1) == Some('=') {
+                        return self
+                            .emit_from_chars(Token::AssignmentOperator { value: "+=".into() }, 2);
+                    }
+                    if self.is_unary_operator() {
+                        // Signed number
+                        if Self::is_digit(self.char(1), 10) {
+                            return self.lex_number();
+                        }
+                        // Unary +
+                        self.lex_state = LexState::EXPR_BEG;
+                        return self.emit_from_chars(Token::OpUnaryPlus, 1);
                     }
-                    todo!()
+                    // Binary +
+                    self.lex_state = LexState::EXPR_BEG;
+                    return self.emit_from_chars(Token::OpPlus, 1);
                 }
                 '-' => {
                     // parse.y:5004
-                    if Self::is_digit(self.char(1), 10) {
-                        return self.lex_number();
+                    if self.is_after_operator() {
+                        self.lex_state = LexState::EXPR_ARG;
+                        // -@
+                        if self.char(1) == Some('@') {
+                            return self.emit_from_chars(Token::OpUnaryMinus, 2);
+                        }
+                        return self.emit_from_chars(Token::OpMinus, 1);
                     }
-                    todo!()
+                    // -=
+                    if self.char(1) == Some('=') {
+                        return self
+                            .emit_from_chars(Token::AssignmentOperator { value: "-=".into() }, 2);
+                    }
+                    // ->
+                    if self.char(1) == Some('>') {
+                        self.lex_state = LexState::EXPR_ENDFN;
+                        self.nesting_level += 1;
+                        self.lambda_nesting_levels.push(self.nesting_level);
+                        let explicit_parameters = self.is_lambda_parameter_list_ahead(2);
+                        self.lambda_parameters.push(explicit_parameters);
+                        return self.emit_from_chars(Token::Lambda, 2);
+                    }
+                    if self.is_unary_operator() {
+                        // Signed number
+                        if Self::is_digit(self.char(1), 10) {
+                            return self.lex_number();
+                        }
+                        // Unary -
+                        self.lex_state = LexState::EXPR_BEG;
+                        return self.emit_from_chars(Token::OpUnaryMinus, 1);
+                    }
+                    // Binary -
+                    self.lex_state = LexState::EXPR_BEG;
+                    return self.emit_from_chars(Token::OpMinus, 1);
                 }
                 '.' => {
                     // parse.y:5035
+                    let beg = self.is_beg();
+                    let state = self.state();
                     self.lex_state = LexState::EXPR_BEG;
                     if self.char(1) == Some('.') {
                         if self.char(2) == Some('.') {
+                            if self.is_argument_forwarding(beg) {
+                                let location = self.get_pos();
+                                self.require_version(
+                                    RubyVersion::Ruby2_7,
+                                    "argument forwarding",
+                                    location,
+                                )?;
+                            }
                             return self.emit_from_chars(Token::ThreeDot, 3);
                         }
                         return self.emit_from_chars(Token::TwoDot, 2);
@@ -274,7 +594,7 @@ where
                         });
                     }
 
-                    self.lex_state = LexState::EXPR_DOT;
+                    self.lex_state = self.state_after_dot(state);
                     return self.emit_from_chars(Token::Dot, 1);
                 }
                 '0'..='9' => {
@@ -282,59 +602,165 @@ where
                     return self.lex_number();
                 }
                 ')' | ']' | '}' => {
-                    // TODO: parse.y:5284
+                    // parse.y:5284
+                    if c == '}' {
+                        if let Some(result) = self.close_interpolation() {
+                            return result;
+                        }
+                    }
+                    if self.nesting_level == 0 {
+                        return Err(LexicalError {
+                            message: format!("syntax error, unexpected '{}'", c),
+                            location: self.get_pos(),
+                        });
+                    }
+                    self.close_brace_block();
                     self.nesting_level -= 1;
-                    self.lex_state = if c == ')' {
-                        LexState::EXPR_ENDFN
-                    } else {
-                        LexState::EXPR_END
-                    };
-                    let token = match c {
-                        ')' => Token::RightParen,
-                        ']' => Token::RightBracket,
-                        '}' => Token::RightBrace,
-                        _ => todo!(),
+                    self.pop_nesting();
+                    let (token, state) = match c {
+                        ')' => (Token::RightParen, LexState::EXPR_ENDFN),
+                        ']' => (Token::RightBracket, LexState::EXPR_END),
+                        _ => (Token::RightBrace, LexState::EXPR_END),
                     };
+                    self.lex_state = state;
                     return self.emit_from_chars(token, 1);
                 }
                 ':' => {
-                    // TODO: parse.y:5297
-                    todo!()
+                    // parse.y:5297
+                    return self.lex_colon();
                 }
                 '/' => {
-                    // TODO: parse.y:5321
-                    todo!()
+                    // parse.y:5321
+                    if self.is_beg() {
+                        return self.lex_string(StringType::REGEXP);
+                    }
+                    // /=
+                    if self.char(1) == Some('=') {
+                        return self
+                            .emit_from_chars(Token::AssignmentOperator { value: "/=".into() }, 2);
+                    }
+                    if let Some(c) = self.char(1) {
+                        if self.is_spcarg(c) {
+                            self.warn(
+                                WarningKind::AmbiguousFirstArgument,
+                                "ambiguous first argument; put parentheses or a space even after `/' operator",
+                            );
+                            return self.lex_string(StringType::REGEXP);
+                        }
+                    }
+                    // /
+                    self.set_lexer_newline_state();
+                    return self.emit_from_chars(Token::OpDivide, 1);
                 }
                 '^' => {
-                    // TODO: parse.y:5344
-                    todo!()
+                    // parse.y:5344
+                    // ^=
+                    if self.char(1) == Some('=') {
+                        return self
+                            .emit_from_chars(Token::AssignmentOperator { value: "^=".into() }, 2);
+                    }
+                    // ^
+                    self.set_lexer_newline_state();
+                    return self.emit_from_chars(Token::OpBinXor, 1);
                 }
                 ';' => {
                     // parse.y:5359
+                    self.end_statement();
+                    self.method_definition = None;
+                    self.arrow_allowed = false;
                     self.lex_state = LexState::EXPR_BEG;
+                    self.command_state = true;
                     return self.emit_from_chars(Token::Semicolon, 1);
                 }
                 ',' => {
                     // parse.y:5359
-                    self.lex_state = LexState::EXPR_BEG;
+                    self.lex_state = LexState::EXPR_BEG | LexState::EXPR_LABEL;
                     return self.emit_from_chars(Token::Comma, 1);
                 }
                 '~' => {
                     // parse.y:5367
+                    // ~@
+                    if self.is_after_operator() && self.char(1) == Some('@') {
+                        self.lex_state = LexState::EXPR_ARG;
+                        return self.emit_from_chars(Token::OpBinComplement, 2);
+                    }
                     self.set_lexer_newline_state();
                     return self.emit_from_chars(Token::OpBinComplement, 1);
                 }
                 '(' => {
-                    // TODO: parse.y:5379
-                    todo!()
+                    // parse.y:5379
+                    let token = if self.seen_whitespace
+                        && (self.is_arg() || self.state() == LexState::EXPR_END)
+                    {
+                        Token::LeftParenArg
+                    } else {
+                        Token::LeftParen
+                    };
+                    self.nesting_level += 1;
+                    self.push_nesting();
+                    self.lex_state = LexState::EXPR_BEG | LexState::EXPR_LABEL;
+                    return self.emit_from_chars(token, 1);
                 }
                 '[' => {
-                    // TODO: parse.y:5395
-                    todo!()
+                    // parse.y:5395
+                    if self.is_after_operator() {
+                        self.lex_state = LexState::EXPR_ARG;
+                        if self.char(1) == Some(']') {
+                            // []=
+                            if self.char(2) == Some('=') {
+                                return self.emit_from_chars(Token::OpElementSet, 3);
+                            }
+                            // []
+                            return self.emit_from_chars(Token::OpElementGet, 2);
+                        }
+                        self.nesting_level += 1;
+                        self.push_nesting();
+                        return self.emit_from_chars(Token::LeftBracket, 1);
+                    }
+                    self.nesting_level += 1;
+                    self.push_nesting();
+                    self.lex_state = LexState::EXPR_BEG | LexState::EXPR_LABEL;
+                    return self.emit_from_chars(Token::LeftBracket, 1);
                 }
                 '{' => {
-                    // TODO: parse.y:5420
-                    todo!()
+                    // parse.y:5420
+                    let token = if self.lambda_nesting_levels.last() == Some(&self.nesting_level) {
+                        // The body of a lambda literal
+                        self.lambda_nesting_levels.pop();
+                        self.nesting_level -= 1;
+                        Token::LambdaBegin
+                    } else if self.is_labeled() {
+                        // The value of a label
+                        Token::LeftBrace
+                    } else if self.is_arg()
+                        || self.state() == LexState::EXPR_END
+                        || self.state() == LexState::EXPR_ENDFN
+                    {
+                        Token::LeftBraceBlock
+                    } else if self.state() == LexState::EXPR_ENDARG {
+                        Token::LeftBraceArg
+                    } else {
+                        Token::LeftBrace
+                    };
+                    self.nesting_level += 1;
+                    self.push_nesting();
+                    let explicit_parameters = match token {
+                        Token::LambdaBegin => Some(self.lambda_parameters.pop().unwrap_or(false)),
+                        Token::LeftBraceBlock | Token::LeftBraceArg => {
+                            Some(self.is_parameter_list_ahead(1))
+                        }
+                        _ => None,
+                    };
+                    if let Some(explicit_parameters) = explicit_parameters {
+                        self.open_block(BlockEnd::Brace(self.nesting_level), explicit_parameters);
+                    }
+                    if token == Token::LeftBrace {
+                        self.lex_state = LexState::EXPR_BEG | LexState::EXPR_LABEL;
+                    } else {
+                        self.command_state = true;
+                        self.lex_state = LexState::EXPR_BEG;
+                    }
+                    return self.emit_from_chars(token, 1);
                 }
                 '\\' => {
                     // parse.y:5440
@@ -344,12 +770,27 @@ where
                     return self.emit_from_chars(Token::Backslash, 1);
                 }
                 '%' => {
-                    // TODO: parse.y:5451
-                    todo!()
+                    // parse.y:5451
+                    if self.is_beg() {
+                        return self.lex_percent_literal();
+                    }
+                    // %=
+                    if self.char(1) == Some('=') {
+                        return self
+                            .emit_from_chars(Token::AssignmentOperator { value: "%=".into() }, 2);
+                    }
+                    if let Some(c) = self.char(1) {
+                        if self.is_spcarg(c) {
+                            return self.lex_percent_literal();
+                        }
+                    }
+                    // %
+                    self.set_lexer_newline_state();
+                    return self.emit_from_chars(Token::OpModulus, 1);
                 }
                 '$' => {
-                    // TODO: parse.y:5539
-                    todo!()
+                    // parse.y:5539
+                    return self.lex_global_variable();
                 }
                 '@' => {
                     // parse.y:5633
@@ -383,7 +824,7 @@ where
                         Some(c) => {
                             if Self::is_identchar(c) {
                                 let prefix = if idx == 1 { "@" } else { "@@" };
-                                return self.lex_identifier(prefix.to_owned());
+                                return self.lex_identifier(prefix);
                             }
                             return self.emit_from_chars(Token::At, 1);
                         }
@@ -391,27 +832,139 @@ where
                 }
                 _ => {
                     // TODO: parse.y:5679
-                    return self.lex_identifier("".to_owned());
+                    return self.lex_identifier("");
                 }
             }
         }
-        // End of file
+        // End of file, which may fall within code interpolated into a literal
+        let term = self.lex_strterm.pop();
+        if let Some(term) = term.or_else(|| self.pending_heredocs.pop_front()) {
+            self.abandon_literals();
+            return Err(Self::unterminated_literal(&term));
+        }
         Ok((self.get_pos(), Token::EndOfFile, self.get_pos()))
     }
 
     /// Lexes a single-line comment
-    fn lex_single_line_comment(&mut self) -> LexResult {
+    fn lex_single_line_comment(&mut self) -> LexResult<'src> {
         let tok_start = self.get_pos();
-        let mut content = String::new();
         self.next_char(); // Discard the '#'
-        while self.char(0) != Some('\n') && self.char(0) != None {
-            content.push(self.next_char().unwrap());
-        }
+        let content_start = self.get_pos();
+        self.skip_while(|c| c != '\n');
+        let content = self.slice_from(&content_start);
+        self.read_magic_comment(&content, &tok_start);
         return Ok((tok_start, Token::Comment { value: content }, self.get_pos()));
     }
 
+    /// Returns the values set by the magic comments lexed so far
+    pub fn magic_comments(&self) -> &MagicComments {
+        &self.magic_comments
+    }
+
+    /// Returns the encoding of the source being lexed
+    pub fn source_encoding(&self) -> SourceEncoding {
+        self.encoding
+    }
+
+    /// Applies the magic comment held by a single-line comment, if any
+    ///
+    /// The encoding may only be set on the first line (or the second line, after a shebang),
+    /// `frozen_string_literal` must precede the first token and `shareable_constant_value` must
+    /// be on a line of its own.
+    fn read_magic_comment(&mut self, content: &str, tok_start: &Location) {
+        if tok_start.line() == 1 && tok_start.col() == 1 && content.starts_with('!') {
+            self.shebang = true;
+        }
+        let comment_line = self.last_token_line != Some(tok_start.line());
+        let top_line = self.last_token_line.is_none()
+            && (tok_start.line() == 1 || (tok_start.line() == 2 && self.shebang));
+
+        let pairs = parse_magic_comment(content);
+        if pairs.is_empty() {
+            if top_line {
+                if let Some(encoding) = parse_coding(content) {
+                    self.magic_comments.encoding = Some(encoding);
+                }
+            }
+            return;
+        }
+        for (name, value) in pairs {
+            match name.as_str() {
+                "coding" | "encoding" if top_line => {
+                    self.magic_comments.encoding = Some(value);
+                }
+                "frozen_string_literal" => {
+                    if self.last_token_line.is_some() {
+                        self.warn_at(
+                            WarningKind::IgnoredMagicComment,
+                            "`frozen_string_literal' is ignored after any tokens",
+                            tok_start.clone(),
+                        );
+                    } else if let Some(value) = self.magic_comment_bool(&name, &value, tok_start) {
+                        self.magic_comments.frozen_string_literal = Some(value);
+                    }
+                }
+                "warn_indent" => {
+                    if let Some(value) = self.magic_comment_bool(&name, &value, tok_start) {
+                        self.magic_comments.warn_indent = Some(value);
+                    }
+                }
+                "shareable_constant_value" => {
+                    if !comment_line {
+                        self.warn_at(
+                            WarningKind::IgnoredMagicComment,
+                            "`shareable_constant_value' is ignored unless in comment-only line",
+                            tok_start.clone(),
+                        );
+                    } else if let Some(value) = ShareableConstantValue::from_name(&value) {
+                        self.magic_comments.shareable_constant_value = Some(value);
+                    } else {
+                        self.invalid_magic_comment_value(&name, &value, tok_start);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the value of a boolean magic comment, warning if it is invalid
+    fn magic_comment_bool(
+        &mut self,
+        name: &str,
+        value: &str,
+        tok_start: &Location,
+    ) -> Option<bool> {
+        match value.to_ascii_lowercase().as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => {
+                self.invalid_magic_comment_value(name, value, tok_start);
+                None
+            }
+        }
+    }
+
+    fn invalid_magic_comment_value(&mut self, name: &str, value: &str, tok_start: &Location) {
+        self.warn_at(
+            WarningKind::InvalidMagicCommentValue,
+            &format!("invalid value for {}: {}", name, value),
+            tok_start.clone(),
+        );
+    }
+
+    /// Checks if `=begin` followed by whitespace or a newline starts at the beginning of the line
+    fn is_multi_line_comment_start(&self) -> bool {
+        if self.get_pos().col() != 1 || !self.lookahead("=begin") {
+            return false;
+        }
+        match self.char(6) {
+            Some(c) => Self::is_space(c),
+            None => true,
+        }
+    }
+
     /// Lexes a multi-line comment
-    fn lex_multi_line_comment(&mut self) -> LexResult {
+    fn lex_multi_line_comment(&mut self) -> LexResult<'src> {
         let tok_start = self.get_pos();
         let mut str = String::new();
 
@@ -423,16 +976,18 @@ where
         // Grab everything until '=end ' is found at the beginning of a line
         loop {
             // Check for the end of the multi-line comment and break if found
-            if self.get_pos().col() == 1 && self.chars(4) == Some("=end".to_owned()) {
-                if let Some(char) = self.char(4) {
-                    if Self::is_whitespace(char) || char == '\n' {
-                        // Discard the '=end '
-                        for _ in 1..=5 {
-                            self.next_char();
-                        }
-                        break;
-                    }
+            if self.get_pos().col() == 1
+                && self.lookahead("=end")
+                && self.char(4).is_none_or(Self::is_space)
+            {
+                // Discard the '=end' and the whitespace following it
+                for _ in 1..=4 {
+                    self.next_char();
+                }
+                if self.char(0).is_some_and(Self::is_whitespace) {
+                    self.next_char();
                 }
+                break;
             }
 
             // Otherwise, consume the next character
@@ -460,66 +1015,146 @@ where
         // Return the lexed result
         Ok((
             tok_start,
-            Token::Comment {
-                value: str.to_owned(),
-            },
+            Token::EmbeddedDocument { value: str.into() },
             self.get_pos(),
         ))
     }
+}
 
-    fn warn(&self, _msg: &str) {
-        // Do something with the string
+impl Lexer<'static> {
+    /// Initializes a lexer for source code provided as bytes
+    ///
+    /// The source is decoded from the encoding given by its UTF-8 byte order mark, its magic
+    /// encoding comment or otherwise UTF-8. The values of string tokens hold the bytes of the
+    /// literals in the lexer's `source_encoding()`, as they appear in the source except where
+    /// produced by escape sequences. Locations are counted within the decoded source.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LexicalError> {
+        let detected = detect_encoding(bytes);
+        let bytes = &bytes[detected.bom_len..];
+        let encoding = match detected.name {
+            None => SourceEncoding::default(),
+            Some(name) => match SourceEncoding::for_name(&name) {
+                Some(encoding) => encoding,
+                None => {
+                    return Err(LexicalError {
+                        message: format!("unknown encoding name: {}", name),
+                        location: Location::new(detected.line, 1),
+                    })
+                }
+            },
+        };
+        let source = match encoding.decode(bytes) {
+            Ok(source) => source,
+            Err(prefix) => {
+                let mut location = Location::default();
+                location.reset();
+                prefix.chars().for_each(|c| location.advance(c));
+                return Err(LexicalError {
+                    message: format!("invalid multibyte char ({})", encoding),
+                    location,
+                });
+            }
+        };
+        let encoded_source = match encoding {
+            SourceEncoding::Utf8 => None,
+            encoding => Some(EncodedSource::new(encoding, bytes, &source)),
+        };
+        let mut lexer = Lexer::with_source(Cow::Owned(source));
+        lexer.encoding = encoding;
+        lexer.encoded_source = encoded_source;
+        Ok(lexer)
     }
 }
 
-pub fn make_tokenizer(source: &str) -> impl Iterator<Item = LexResult> + '_ {
-    let nlh = NewlinesHandler::new(source.chars());
-    Lexer::new(nlh)
+pub fn make_tokenizer<'src>(source: &'src str) -> impl Iterator<Item = LexResult<'src>> + 'src {
+    Lexer::new(source)
+}
+
+/// Describes a reserved word (mruby's `kwtable`)
+#[derive(Clone, Debug)]
+struct Keyword {
+    /// The token emitted for the keyword
+    token: Token<'static>,
+    /// The token emitted when the keyword is used as a modifier
+    modifier: Token<'static>,
+    /// The lexer's state after the keyword
+    state: LexState,
+}
+
+impl Keyword {
+    fn new(token: Token<'static>, state: LexState) -> Self {
+        Keyword {
+            modifier: token.clone(),
+            token,
+            state,
+        }
+    }
+
+    fn with_modifier(token: Token<'static>, modifier: Token<'static>, state: LexState) -> Self {
+        Keyword {
+            token,
+            modifier,
+            state,
+        }
+    }
 }
 
 // 8.7.2 - Keywords (alphanumerically)
-// pub fn get_keywords() -> HashMap<String, Token> {
-//     let mut keywords: HashMap<String, Token> = HashMap::new();
-//     keywords.insert(String::from("__LINE__"), Token::KwLINE);
-//     keywords.insert(String::from("__ENCODING__"), Token::KwENCODING);
-//     keywords.insert(String::from("__FILE__"), Token::KwFILE);
-//     keywords.insert(String::from("BEGIN"), Token::KwBEGIN);
-//     keywords.insert(String::from("END"), Token::KwEND);
-//     keywords.insert(String::from("alias"), Token::KwAlias);
-//     keywords.insert(String::from("and"), Token::KwAnd);
-//     keywords.insert(String::from("begin"), Token::KwBegin);
-//     keywords.insert(String::from("break"), Token::KwBreak);
-//     keywords.insert(String::from("case"), Token::KwCase);
-//     keywords.insert(String::from("class"), Token::KwClass);
-//     keywords.insert(String::from("def"), Token::KwDef);
-//     keywords.insert(String::from("defined?"), Token::KwDefined);
-//     keywords.insert(String::from("do"), Token::KwDo);
-//     keywords.insert(String::from("else"), Token::KwElse);
-//     keywords.insert(String::from("elsif"), Token::KwElsif);
-//     keywords.insert(String::from("end"), Token::KwEnd);
-//     keywords.insert(String::from("ensure"), Token::KwEnsure);
-//     keywords.insert(String::from("for"), Token::KwFor);
-//     keywords.insert(String::from("false"), Token::KwFalse);
-//     keywords.insert(String::from("if"), Token::KwIf);
-//     keywords.insert(String::from("in"), Token::KwIn);
-//     keywords.insert(String::from("module"), Token::KwModule);
-//     keywords.insert(String::from("next"), Token::KwNext);
-//     keywords.insert(String::from("nil"), Token::KwNil);
-//     keywords.insert(String::from("not"), Token::KwNot);
-//     keywords.insert(String::from("or"), Token::KwOr);
-//     keywords.insert(String::from("redo"), Token::KwRedo);
-//     keywords.insert(String::from("rescue"), Token::KwRescue);
-//     keywords.insert(String::from("retry"), Token::KwRetry);
-//     keywords.insert(String::from("return"), Token::KwReturn);
-//     keywords.insert(String::from("self"), Token::KwSelf);
-//     keywords.insert(String::from("super"), Token::KwSuper);
-//     keywords.insert(String::from("then"), Token::KwThen);
-//     keywords.insert(String::from("true"), Token::KwTrue);
-//     keywords.insert(String::from("undef"), Token::KwUndef);
-//     keywords.insert(String::from("unless"), Token::KwUnless);
-//     keywords.insert(String::from("until"), Token::KwUntil);
-//     keywords.insert(String::from("when"), Token::KwWhen);
-//     keywords.insert(String::from("while"), Token::KwWhile);
-//     keywords.insert(String::from("yield"), Token::KwYield);
-//     keywords
-// }
+/// Returns the reserved word with the given name, if any
+fn get_keyword(name: &str) -> Option<Keyword> {
+    let keyword = match name {
+        "__LINE__" => Keyword::new(Token::KwLINE, LexState::EXPR_END),
+        "__ENCODING__" => Keyword::new(Token::KwENCODING, LexState::EXPR_END),
+        "__FILE__" => Keyword::new(Token::KwFILE, LexState::EXPR_END),
+        "BEGIN" => Keyword::new(Token::KwBEGIN, LexState::EXPR_END),
+        "END" => Keyword::new(Token::KwEND, LexState::EXPR_END),
+        "alias" => Keyword::new(Token::KwAlias, LexState::EXPR_FNAME),
+        "and" => Keyword::new(Token::KwAnd, LexState::EXPR_VALUE),
+        "begin" => Keyword::new(Token::KwBegin, LexState::EXPR_BEG),
+        "break" => Keyword::new(Token::KwBreak, LexState::EXPR_MID),
+        "case" => Keyword::new(Token::KwCase, LexState::EXPR_VALUE),
+        "class" => Keyword::new(Token::KwClass, LexState::EXPR_CLASS),
+        "def" => Keyword::new(Token::KwDef, LexState::EXPR_FNAME),
+        "defined?" => Keyword::new(Token::KwDefined, LexState::EXPR_ARG),
+        "do" => Keyword::new(Token::KwDo, LexState::EXPR_BEG),
+        "else" => Keyword::new(Token::KwElse, LexState::EXPR_BEG),
+        "elsif" => Keyword::new(Token::KwElsif, LexState::EXPR_VALUE),
+        "end" => Keyword::new(Token::KwEnd, LexState::EXPR_END),
+        "ensure" => Keyword::new(Token::KwEnsure, LexState::EXPR_BEG),
+        "for" => Keyword::new(Token::KwFor, LexState::EXPR_VALUE),
+        "false" => Keyword::new(Token::KwFalse, LexState::EXPR_END),
+        "if" => Keyword::with_modifier(Token::KwIf, Token::KwIfModifier, LexState::EXPR_VALUE),
+        "in" => Keyword::new(Token::KwIn, LexState::EXPR_VALUE),
+        "module" => Keyword::new(Token::KwModule, LexState::EXPR_VALUE),
+        "next" => Keyword::new(Token::KwNext, LexState::EXPR_MID),
+        "nil" => Keyword::new(Token::KwNil, LexState::EXPR_END),
+        "not" => Keyword::new(Token::KwNot, LexState::EXPR_ARG),
+        "or" => Keyword::new(Token::KwOr, LexState::EXPR_VALUE),
+        "redo" => Keyword::new(Token::KwRedo, LexState::EXPR_END),
+        "rescue" => {
+            Keyword::with_modifier(Token::KwRescue, Token::KwRescueModifier, LexState::EXPR_MID)
+        }
+        "retry" => Keyword::new(Token::KwRetry, LexState::EXPR_END),
+        "return" => Keyword::new(Token::KwReturn, LexState::EXPR_MID),
+        "self" => Keyword::new(Token::KwSelf, LexState::EXPR_END),
+        "super" => Keyword::new(Token::KwSuper, LexState::EXPR_ARG),
+        "then" => Keyword::new(Token::KwThen, LexState::EXPR_BEG),
+        "true" => Keyword::new(Token::KwTrue, LexState::EXPR_END),
+        "undef" => Keyword::new(Token::KwUndef, LexState::EXPR_FNAME),
+        "unless" => Keyword::with_modifier(
+            Token::KwUnless,
+            Token::KwUnlessModifier,
+            LexState::EXPR_VALUE,
+        ),
+        "until" => {
+            Keyword::with_modifier(Token::KwUntil, Token::KwUntilModifier, LexState::EXPR_VALUE)
+        }
+        "when" => Keyword::new(Token::KwWhen, LexState::EXPR_VALUE),
+        "while" => {
+            Keyword::with_modifier(Token::KwWhile, Token::KwWhileModifier, LexState::EXPR_VALUE)
+        }
+        "yield" => Keyword::new(Token::KwYield, LexState::EXPR_ARG),
+        _ => return None,
+    };
+    Some(keyword)
+}
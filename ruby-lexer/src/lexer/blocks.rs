@@ -0,0 +1,127 @@
+// This module tracks the blocks being lexed, which determine whether `it` and `_1` refer to the
+// implicit parameters of a block or are ordinary identifiers.
+
+use super::{Lexer, Token};
+
+/// Describes how the end of a block is found
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum BlockEnd {
+    /// The `}` closing the given nesting level
+    Brace(usize),
+    /// The `end` closing the given number of open keyword constructs
+    Keyword(usize),
+}
+
+/// A block (or the body of a lambda) being lexed
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Block {
+    end: BlockEnd,
+    /// Whether the block declares its parameters (e.g. `|x|`, `||` or `->(x)`)
+    explicit_parameters: bool,
+    /// Whether a local variable named `it` is assigned within the block
+    it_assigned: bool,
+}
+
+impl<'src> Lexer<'src> {
+    /// Marks the start of a block, which ends as given
+    pub(crate) fn open_block(&mut self, end: BlockEnd, explicit_parameters: bool) {
+        self.blocks.push(Block {
+            end,
+            explicit_parameters,
+            it_assigned: false,
+        });
+    }
+
+    /// Marks the end of the brace block at the current nesting level, if any
+    pub(crate) fn close_brace_block(&mut self) {
+        if self.blocks.last().map(|block| block.end) == Some(BlockEnd::Brace(self.nesting_level)) {
+            self.blocks.pop();
+        }
+    }
+
+    /// Tracks the keyword constructs closed by `end`, along with the `do` blocks among them
+    pub(crate) fn track_block_keyword(&mut self, keyword: &Token<'src>) {
+        match keyword {
+            Token::KwDo | Token::KwDoForBlock => {
+                self.keyword_depth += 1;
+                let explicit_parameters = self.is_parameter_list_ahead(0);
+                self.open_block(BlockEnd::Keyword(self.keyword_depth), explicit_parameters);
+            }
+            Token::KwDoForLambda => {
+                self.keyword_depth += 1;
+                let explicit_parameters = self.lambda_parameters.pop().unwrap_or(false);
+                self.open_block(BlockEnd::Keyword(self.keyword_depth), explicit_parameters);
+            }
+            Token::KwClass
+            | Token::KwModule
+            | Token::KwDef
+            | Token::KwBegin
+            | Token::KwCase
+            | Token::KwIf
+            | Token::KwUnless
+            | Token::KwWhile
+            | Token::KwUntil
+            | Token::KwFor => self.keyword_depth += 1,
+            Token::KwEnd => {
+                if self.blocks.last().map(|block| block.end)
+                    == Some(BlockEnd::Keyword(self.keyword_depth))
+                {
+                    self.blocks.pop();
+                }
+                self.keyword_depth = self.keyword_depth.saturating_sub(1);
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks if a block whose opening `{` or `do` ends at the given index among the upcoming
+    /// characters declares its parameters between `|` bars
+    pub(crate) fn is_parameter_list_ahead(&self, index: usize) -> bool {
+        let rest = &self.source[self.location.offset() + index..];
+        rest.trim_start_matches(|c| Self::is_whitespace(c) || c == '\n')
+            .starts_with('|')
+    }
+
+    /// Checks if a lambda whose `->` ends at the given index among the upcoming characters
+    /// declares its parameters (`->(x)` or `-> x`)
+    pub(crate) fn is_lambda_parameter_list_ahead(&self, index: usize) -> bool {
+        let rest = &self.source[self.location.offset() + index..];
+        let rest = rest.trim_start_matches(Self::is_whitespace);
+        let do_block = rest.starts_with("do")
+            && !rest[2..].starts_with(|c| Self::is_identchar(c) || c == '?' || c == '!');
+        !rest.is_empty() && !rest.starts_with('{') && !do_block
+    }
+
+    /// Checks if the innermost block declares no parameters, so that `it` and `_1` may refer to
+    /// its implicit parameters
+    pub(crate) fn is_in_parameterless_block(&self) -> bool {
+        self.blocks
+            .last()
+            .is_some_and(|block| !block.explicit_parameters)
+    }
+
+    /// Checks if `it` is a local variable assigned within the innermost block, the blocks
+    /// enclosing it or outside any block
+    pub(crate) fn is_it_assigned(&self) -> bool {
+        self.it_assigned || self.blocks.iter().any(|block| block.it_assigned)
+    }
+
+    /// Checks if the identifier just lexed is assigned to (e.g. `it = 1` or `it ||= 1`)
+    pub(crate) fn is_assignment_ahead(&self) -> bool {
+        let rest = &self.source[self.location.offset()..];
+        let rest = rest.trim_start_matches(Self::is_whitespace);
+        let operators = [
+            "+=", "-=", "*=", "/=", "%=", "**=", "|=", "&=", "^=", "<<=", ">>=", "||=", "&&=",
+        ];
+        (rest.starts_with('=') && !rest[1..].starts_with(&['=', '~', '>'][..]))
+            || operators.iter().any(|operator| rest.starts_with(operator))
+    }
+
+    /// Records that a local variable named `it` is assigned
+    pub(crate) fn assign_it(&mut self) {
+        match self.blocks.last_mut() {
+            Some(block) => block.it_assigned = true,
+            None => self.it_assigned = true,
+        }
+    }
+}
@@ -1,16 +1,39 @@
-use super::{BUFFER_SIZE, LexResult, LexState, Lexer, Location, Token};
+use super::{LexResult, LexState, Lexer, Location, Token};
 use log::trace;
+use std::borrow::Cow;
 
-impl<T> Lexer<T>
-where
-    T: Iterator<Item = char>,
-{
+impl<'src> Lexer<'src> {
     /// This function is used by the iterator implementation to retrieve the next token.
     ///
     /// Depending on what type of token is returned, the lexing state may be adjusted.
-    pub(crate) fn emit_token(&mut self) -> LexResult {
-        let lex_result = self.produce_token();
-        if let Ok((_, token, _)) = &lex_result {
+    #[inline]
+    pub(crate) fn emit_token(&mut self) -> LexResult<'src> {
+        if let Some(token) = self.pending_tokens.pop_front() {
+            return Ok(token);
+        }
+        let state = self.lex_state;
+        let seen_whitespace = self.seen_whitespace;
+        let tok_start = self.get_pos();
+        let lex_result = match self.produce_token() {
+            Err(error) if self.recover_errors => self.recover_from_error(error, tok_start),
+            lex_result => lex_result,
+        };
+        if let Ok((_, token, tok_end)) = &lex_result {
+            // Arguments separated from a method name by whitespace belong to a command call
+            if seen_whitespace
+                && (state == LexState::EXPR_CMDARG || state == LexState::EXPR_ARG)
+                && Self::is_argument_start(token)
+            {
+                self.cmdarg_stack.push(true);
+            }
+            match token {
+                Token::Whitespace
+                | Token::Comment { .. }
+                | Token::EmbeddedDocument { .. }
+                | Token::Newline
+                | Token::LineTerminator => {}
+                _ => self.last_token_line = Some(tok_end.line()),
+            }
             match token {
                 // Assignments should always mark the start of an expression
                 Token::AssignmentOperator { value: _ } => {
@@ -28,40 +51,148 @@ where
         lex_result
     }
 
-    /// Returns the character at the given index within the lexer's buffer.
+    /// Checks if the given token may begin the first argument of a command call
+    fn is_argument_start(token: &Token<'src>) -> bool {
+        matches!(
+            token,
+            Token::Identifier { .. }
+                | Token::Constant { .. }
+                | Token::FunctionIdentifier { .. }
+                | Token::NumberedParameter { .. }
+                | Token::ImplicitParameter
+                | Token::GlobalVariable { .. }
+                | Token::InstanceVariable { .. }
+                | Token::ClassVariable { .. }
+                | Token::RegexNthRef { .. }
+                | Token::RegexBackRef { .. }
+                | Token::Integer { .. }
+                | Token::BigInteger { .. }
+                | Token::Float { .. }
+                | Token::Rational { .. }
+                | Token::Imaginary { .. }
+                | Token::Char { .. }
+                | Token::String { .. }
+                | Token::StringPart { .. }
+                | Token::XString { .. }
+                | Token::Regex { .. }
+                | Token::Symbol { .. }
+                | Token::LabelTag { .. }
+                | Token::HeredocStart { .. }
+                | Token::WordsStart
+                | Token::SymbolsStart
+                | Token::Star
+                | Token::TwoStar
+                | Token::Ampersand
+                | Token::Lambda
+                | Token::OpUnaryPlus
+                | Token::OpUnaryMinus
+                | Token::DoubleColonPrefix
+                | Token::LeftParenArg
+                | Token::LeftBracket
+                | Token::OpNot
+                | Token::KwLINE
+                | Token::KwENCODING
+                | Token::KwFILE
+                | Token::KwBegin
+                | Token::KwCase
+                | Token::KwDef
+                | Token::KwDefined
+                | Token::KwFalse
+                | Token::KwNil
+                | Token::KwNot
+                | Token::KwSelf
+                | Token::KwSuper
+                | Token::KwTrue
+                | Token::KwYield
+        )
+    }
+
+    /// Returns the character at the given index among the upcoming characters.
+    ///
+    /// A `\r\n` line ending counts as a single `\n` character.
+    #[inline]
     pub(crate) fn char(&self, index: usize) -> Option<char> {
-        assert!(index < BUFFER_SIZE);
-        match self.chr.get(index) {
-            Some(c) => *c,
-            None => None,
+        let rest = &self.source.as_bytes()[self.location.offset..];
+        // Most lookahead only concerns ASCII characters
+        match rest.get(index) {
+            Some(&b)
+                if b.is_ascii()
+                    && b != b'\r'
+                    && rest[..index].iter().all(|&b| b.is_ascii() && b != b'\r') =>
+            {
+                Some(b as char)
+            }
+            _ => self.char_slow(index),
         }
     }
 
-    /// Returns the next `n` number of characters as a string (or `None` if EOF encountered).
-    pub(crate) fn chars(&self, n: usize) -> Option<String> {
-        let mut str = String::with_capacity(n);
-        for i in 0..n {
-            match self.chr.get(i) {
-                Some(Some(c)) => str.push(*c),
-                _ => return None,
+    /// Returns the character at the given index by decoding the upcoming characters one by one
+    #[cold]
+    fn char_slow(&self, index: usize) -> Option<char> {
+        let mut chars = self.source[self.location.offset..].chars();
+        let mut i = 0;
+        loop {
+            let c = match chars.next()? {
+                '\r' if chars.as_str().starts_with('\n') => {
+                    chars.next();
+                    '\n'
+                }
+                c => c,
+            };
+            if i == index {
+                return Some(c);
             }
+            i += 1;
         }
-        Some(str)
+    }
+
+    /// Checks if the upcoming characters match the given string.
+    #[inline]
+    pub(crate) fn lookahead(&self, s: &str) -> bool {
+        self.source.as_bytes()[self.location.offset..].starts_with(s.as_bytes())
     }
 
     /// Consumes and returns the next upcoming character, adjusting the lexer's current location.
+    #[inline]
     pub(crate) fn next_char(&mut self) -> Option<char> {
-        // Shift the stack of upcoming characters
-        let c = self.chr.pop_front()?;
-        self.chr.push_back(self.input.next());
+        let rest = &self.source.as_bytes()[self.location.offset..];
+        let c = match *rest.first()? {
+            b'\r' if rest.get(1) == Some(&b'\n') => {
+                // The line ending is consumed as a single character
+                self.location.offset += 1;
+                self.location.char_offset += 1;
+                '\n'
+            }
+            b if b.is_ascii() => b as char,
+            _ => self.source[self.location.offset..].chars().next().unwrap(),
+        };
+        self.location.advance(c);
+        Some(c)
+    }
 
-        // Update the lexer's source location
-        if c == Some('\n') {
-            self.location.newline();
-        } else if let Some(_) = c {
-            self.location.move_right();
+    /// Consumes the upcoming characters for as long as they satisfy the predicate, which must
+    /// not accept a `\n`.
+    #[inline]
+    pub(crate) fn skip_while<F>(&mut self, predicate: F)
+    where
+        F: Fn(char) -> bool,
+    {
+        let rest = &self.source[self.location.offset..];
+        let bytes = rest.as_bytes();
+        let mut len = 0;
+        while len < bytes.len() {
+            let c = match bytes[len] {
+                // A `\r\n` line ending is a single `\n`, which the predicate doesn't accept
+                b'\r' if bytes.get(len + 1) == Some(&b'\n') => break,
+                b if b.is_ascii() => b as char,
+                _ => rest[len..].chars().next().unwrap(),
+            };
+            if !predicate(c) {
+                break;
+            }
+            self.location.advance(c);
+            len += c.len_utf8();
         }
-        c
     }
 
     /// Retrieve's the lexer's current location in the source stream.
@@ -69,31 +200,50 @@ where
         self.location.clone()
     }
 
-    /// Emits tokens from one or more characters in the buffer.
+    /// Returns the source text between the given byte offsets, borrowing it if the source is
+    /// borrowed.
+    pub(crate) fn slice(&self, start: usize, end: usize) -> Cow<'src, str> {
+        match self.source {
+            Cow::Borrowed(source) => Cow::Borrowed(&source[start..end]),
+            Cow::Owned(ref source) => Cow::Owned(source[start..end].to_owned()),
+        }
+    }
+
+    /// Returns the source text from the given location up to the current position.
+    pub(crate) fn slice_from(&self, start: &Location) -> Cow<'src, str> {
+        self.slice(start.offset(), self.location.offset)
+    }
+
+    /// Emits tokens from one or more upcoming characters.
     ///
     /// # Panics
-    ///  * Panics if the number of characters requested is greater than the buffer's size.
-    ///  * Panics if the number of characters requested moves past the end of the buffer's input stream.
-    pub(crate) fn emit_from_chars(&mut self, token: Token, chars: usize) -> LexResult {
+    ///  * Panics if the number of characters requested moves past the end of the input.
+    pub(crate) fn emit_from_chars(&mut self, token: Token<'src>, chars: usize) -> LexResult<'src> {
         let tok_start = self.get_pos();
-        match chars {
-            1..=BUFFER_SIZE => {
-                for _ in 1..=chars {
-                    self.next_char().unwrap();
-                }
-            }
-            _ => panic!("emit_from_chars can only consume up to {} characters at a time", BUFFER_SIZE),
+        for _ in 1..=chars {
+            self.next_char().unwrap();
         }
         Ok((tok_start, token, self.get_pos()))
     }
+
+    /// Emits a token whose value is the source text of one or more upcoming characters.
+    pub(crate) fn emit_chars_as<F>(&mut self, token: F, chars: usize) -> LexResult<'src>
+    where
+        F: FnOnce(Cow<'src, str>) -> Token<'src>,
+    {
+        let tok_start = self.get_pos();
+        for _ in 1..=chars {
+            self.next_char().unwrap();
+        }
+        let value = self.slice_from(&tok_start);
+        Ok((tok_start, token(value), self.get_pos()))
+    }
 }
 
-impl<T> Iterator for Lexer<T>
-where
-    T: Iterator<Item = char>,
-{
-    type Item = LexResult;
+impl<'src> Iterator for Lexer<'src> {
+    type Item = LexResult<'src>;
     /// Produces a `LexResult` unless the end of the file was reached
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         let token = self.emit_token();
         trace!("Lex token {:?}, nesting={:?}", token, self.nesting_level);
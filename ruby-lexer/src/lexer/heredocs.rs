@@ -0,0 +1,267 @@
+use super::strings::{StringTerm, StringType};
+use super::{LexResult, LexState, Lexer, LexicalError, SpannedToken, Token};
+
+/// The number of columns a tab advances to when measuring the indentation of a squiggly heredoc
+const TAB_WIDTH: usize = 8;
+
+/// Tracks the identifier and indentation rules of a heredoc (mruby's `parser_heredoc_info`)
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Heredoc {
+    /// The identifier that terminates the heredoc's body
+    pub identifier: String,
+    /// Whether the terminating identifier may be indented (`<<-` and `<<~`)
+    pub allow_indent: bool,
+    /// Whether the common indentation of the body is removed (`<<~`)
+    pub squiggly: bool,
+    /// Whether the body is positioned at the start of a line
+    pub line_head: bool,
+    /// The lexer's state to be restored once the body has been lexed
+    pub resume_state: LexState,
+    /// The lexer's command state to be restored once the body has been lexed
+    pub resume_command_state: bool,
+}
+
+/// Records the literal indentation found at the start of a line within a squiggly heredoc
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct LineIndent {
+    /// The offset of the line's start within the bytes of its segment's token value
+    pub offset: usize,
+    /// The number of whitespace characters that make up the line's indentation
+    pub len: usize,
+    /// The width of the indentation, in columns
+    pub width: usize,
+    /// Whether the line consists solely of whitespace
+    pub blank: bool,
+}
+
+impl<'src> Lexer<'src> {
+    /// Lexes the start of a heredoc (`<<ID`, `<<-ID`, `<<~ID`, `<<'ID'`, `<<"ID"` or `` <<`ID` ``)
+    ///
+    /// Returns `None` if the upcoming `<<` does not start a heredoc. Otherwise, the heredoc is
+    /// queued so that its body can be lexed once the current line has been lexed.
+    pub(crate) fn lex_heredoc_identifier(&mut self) -> Option<LexResult<'src>> {
+        let mut idx = 2;
+        let (allow_indent, squiggly) = match self.char(2) {
+            Some('-') => (true, false),
+            Some('~') => (true, true),
+            _ => (false, false),
+        };
+        if allow_indent {
+            idx += 1;
+        }
+
+        let kind = match self.char(idx) {
+            Some('\'') => StringType::HEREDOC,
+            Some('"') => StringType::HEREDOC | StringType::DQUOTE,
+            Some('`') => StringType::HEREDOC | StringType::XQUOTE,
+            Some(c) if Self::is_identchar(c) => StringType::HEREDOC | StringType::DQUOTE,
+            _ => return None,
+        };
+
+        // Consume the '<<', '<<-' or '<<~'
+        let tok_start = self.get_pos();
+        for _ in 0..idx {
+            self.next_char();
+        }
+
+        // Consume the identifier
+        let identifier = match self.char(0) {
+            Some(quote @ '\'') | Some(quote @ '"') | Some(quote @ '`') => {
+                self.next_char();
+                let ident_start = self.get_pos();
+                loop {
+                    match self.char(0) {
+                        Some(c) if c == quote => break,
+                        Some('\n') | None => {
+                            return Some(Err(LexicalError {
+                                message: "unterminated here document identifier".to_owned(),
+                                location: tok_start,
+                            }))
+                        }
+                        Some(_) => self.next_char(),
+                    };
+                }
+                let identifier = self.slice_from(&ident_start);
+                self.next_char(); // Consume the closing quote
+                identifier
+            }
+            _ => {
+                let ident_start = self.get_pos();
+                while let Some(c) = self.char(0) {
+                    if !Self::is_identchar(c) {
+                        break;
+                    }
+                    self.next_char();
+                }
+                self.slice_from(&ident_start)
+            }
+        };
+
+        self.pending_heredocs.push_back(StringTerm {
+            kind,
+            term: '\n', // Heredocs are terminated by a line matching their identifier
+            paren: None,
+            nest: 0,
+            start: tok_start.clone(),
+            interpolated: false,
+            embedded: None,
+            heredoc: Some(Heredoc {
+                identifier: identifier.to_string(),
+                allow_indent,
+                squiggly,
+                line_head: true,
+                resume_state: LexState::EXPR_BEG,
+                resume_command_state: false,
+            }),
+        });
+        self.lex_state = LexState::EXPR_END;
+        Some(Ok((
+            tok_start,
+            Token::HeredocStart { value: identifier },
+            self.get_pos(),
+        )))
+    }
+
+    /// Lexes the body of the next queued heredoc, starting at the beginning of a line
+    pub(crate) fn lex_heredoc_body(&mut self) -> Option<LexResult<'src>> {
+        let mut strterm = self.pending_heredocs.pop_front()?;
+        let heredoc = strterm.heredoc.as_mut().unwrap();
+        heredoc.resume_state = self.lex_state;
+        heredoc.resume_command_state = self.prev_command_state;
+        let squiggly = heredoc.squiggly;
+        self.lex_strterm.push(strterm);
+
+        let tok_start = self.get_pos();
+        if !squiggly {
+            return Some(self.lex_string_content(tok_start));
+        }
+
+        // Squiggly heredocs must be lexed in their entirety before their indentation is known
+        let depth = self.lex_strterm.len();
+        let mut tokens: Vec<SpannedToken<'src>> = Vec::new();
+        let mut segments: Vec<(usize, Vec<LineIndent>)> = Vec::new();
+        let mut result = self.lex_string_content(tok_start);
+        loop {
+            match result {
+                Ok(token) => tokens.push(token),
+                Err(error) => return Some(Err(error)),
+            }
+            if let Some(indents) = self.heredoc_indents.take() {
+                segments.push((tokens.len() - 1, indents));
+            }
+            if self.lex_strterm.len() < depth {
+                break;
+            }
+            result = self.emit_token();
+        }
+        Self::dedent_heredoc(&mut tokens, segments);
+
+        let mut tokens = tokens.into_iter();
+        let first = tokens.next();
+        self.pending_tokens.extend(tokens);
+        first.map(Ok)
+    }
+
+    /// Checks for a heredoc's terminating identifier at the start of a line
+    ///
+    /// Returns `true` if the terminating line was found and consumed. Otherwise, any characters
+    /// consumed while searching for the identifier are appended to the heredoc's contents, and the
+    /// line's indentation is recorded for squiggly heredocs.
+    pub(crate) fn lex_heredoc_line_head(
+        &mut self,
+        heredoc: &Heredoc,
+        buf: &mut Vec<u8>,
+        indents: &mut Vec<LineIndent>,
+    ) -> bool {
+        let line_start = self.location.offset();
+        let mut consumed = String::new();
+        let mut width = 0;
+        if heredoc.allow_indent {
+            while let Some(c) = self.char(0) {
+                match c {
+                    ' ' => width += 1,
+                    '\t' => width = TAB_WIDTH * (width / TAB_WIDTH + 1),
+                    _ => break,
+                }
+                consumed.push(self.next_char().unwrap());
+            }
+        }
+        let indent_len = consumed.len();
+
+        // Consume as much of the identifier as matches
+        let mut matched = true;
+        for expected in heredoc.identifier.chars() {
+            if self.char(0) != Some(expected) {
+                matched = false;
+                break;
+            }
+            consumed.push(self.next_char().unwrap());
+        }
+        if matched && (self.char(0) == Some('\n') || self.char(0).is_none()) {
+            self.next_char();
+            return true;
+        }
+
+        if heredoc.squiggly {
+            indents.push(LineIndent {
+                offset: buf.len(),
+                len: indent_len,
+                width,
+                blank: consumed.len() == indent_len && self.char(0) == Some('\n'),
+            });
+        }
+        self.push_source(buf, line_start, self.location.offset());
+        false
+    }
+
+    /// Removes the common indentation from the segments of a squiggly heredoc
+    ///
+    /// Lines consisting solely of whitespace are not considered when determining the width of
+    /// the indentation to be removed. Tabs are treated as advancing to the next multiple of 8
+    /// columns, and are only removed if they fall entirely within the removed width. The
+    /// indentation is removed from the bytes of each segment's token value.
+    fn dedent_heredoc(tokens: &mut [SpannedToken<'src>], segments: Vec<(usize, Vec<LineIndent>)>) {
+        let width = segments
+            .iter()
+            .flat_map(|(_, indents)| indents)
+            .filter(|indent| !indent.blank)
+            .map(|indent| indent.width)
+            .min()
+            .unwrap_or(usize::MAX);
+
+        for (index, indents) in segments {
+            let value = match &mut tokens[index].1 {
+                Token::String { value }
+                | Token::StringPart { value }
+                | Token::StringMid { value }
+                | Token::XString { value } => value,
+                _ => continue,
+            };
+            for indent in indents.iter().rev() {
+                let bytes = &value.as_bytes()[indent.offset..indent.offset + indent.len];
+                let mut col = 0;
+                let mut len = 0;
+                for b in bytes {
+                    if col >= width {
+                        break;
+                    }
+                    match b {
+                        b' ' => col += 1,
+                        b'\t' => {
+                            let next = TAB_WIDTH * (col / TAB_WIDTH + 1);
+                            if next > width {
+                                break;
+                            }
+                            col = next;
+                        }
+                        _ => break,
+                    }
+                    len += 1;
+                }
+                if len > 0 {
+                    value.bytes_mut().drain(indent.offset..indent.offset + len);
+                }
+            }
+        }
+    }
+}
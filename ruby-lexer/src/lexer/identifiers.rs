@@ -1,5 +1,6 @@
-use super::{LexResult, LexState, Lexer, Location, SpannedToken, Token, BUFFER_SIZE};
-use log::trace;
+use super::{
+    get_keyword, Keyword, LexResult, LexState, Lexer, LexicalError, Location, Token, WarningKind,
+};
 
 enum IdentifierType {
     Global,
@@ -11,10 +12,7 @@ enum IdentifierType {
     Identifier,
 }
 
-impl<T> Lexer<T>
-where
-    T: Iterator<Item = char>,
-{
+impl<'src> Lexer<'src> {
     /// Checks if the given character is a valid Ruby identifier character
     ///
     /// Identifying characters include `[a-zA-Z0-9_]` and non-ascii characters
@@ -22,35 +20,39 @@ where
         c.is_ascii_alphanumeric() || c == '_' || !c.is_ascii()
     }
 
+    /// Checks if the given character may follow `$` as a single-character global variable name
+    pub(crate) fn is_global_name_punct(c: char) -> bool {
+        "~*$?!@/\\;,.=:<>\"&`'+0".contains(c)
+    }
+
     /// Lexes and returns an identifier or a language keyword
-    pub(crate) fn lex_identifier(&mut self, prefix: String) -> LexResult {
+    pub(crate) fn lex_identifier(&mut self, prefix: &str) -> LexResult<'src> {
         // Check for the program end token followed by a newline or EOF
         let start_pos = self.get_pos();
-        if prefix == "" && start_pos.col() == 1 && self.chars(7) == Some("__END__".to_owned()) {
-            if self.char(7) == Some('\n') || self.char(7) == None {
-                return self.emit_from_chars(Token::EndOfProgramMarker, 7);
-            }
+        if prefix.is_empty()
+            && start_pos.col() == 1
+            && self.lookahead("__END__")
+            && matches!(self.char(7), Some('\n') | None)
+        {
+            return self.emit_from_chars(Token::EndOfProgramMarker, 7);
         }
 
         // Consume any prefix characters (@, @@, $)
-        let mut ident = prefix;
-        for _ in 0..ident.chars().count() {
+        for _ in 0..prefix.chars().count() {
             self.next_char();
         }
         // Consume any identifier characters
-        while let Some(c) = self.char(0) {
-            if Self::is_identchar(c) {
-                ident.push(self.next_char().unwrap());
-                continue;
-            }
-            break;
-        }
-        // Add a method-like identifying character if not followed by '='
+        self.skip_while(Self::is_identchar);
+        // Add a method-like identifying character (only valid for local names) if not followed by '='
         let mut method_only = false;
-        if (self.char(0) == Some('!') || self.char(0) == Some('?')) && self.char(1) != Some('=') {
-            ident.push(self.next_char().unwrap());
+        if prefix.is_empty()
+            && (self.char(0) == Some('!') || self.char(0) == Some('?'))
+            && self.char(1) != Some('=')
+        {
+            self.next_char();
             method_only = true;
         }
+        let mut ident = self.slice_from(&start_pos);
 
         let token_type: IdentifierType = match ident.chars().nth(0).unwrap() {
             '$' => {
@@ -58,6 +60,7 @@ where
                 IdentifierType::Global
             }
             '@' => {
+                self.lex_state = LexState::EXPR_END;
                 if ident.chars().nth(1) == Some('@') {
                     IdentifierType::Class
                 } else {
@@ -65,62 +68,214 @@ where
                 }
             }
             _ => {
-                if method_only {
+                let result = if method_only {
                     IdentifierType::MethodOnly
+                } else if self.state() == LexState::EXPR_FNAME
+                    && self.char(0) == Some('=')
+                    && self.char(1) != Some('~')
+                    && self.char(1) != Some('>')
+                    && (self.char(1) != Some('=') || self.char(2) == Some('>'))
+                {
+                    self.next_char();
+                    ident = self.slice_from(&start_pos);
+                    IdentifierType::AssignmentLike
+                } else if ident.chars().nth(0).unwrap().is_ascii_uppercase() {
+                    IdentifierType::Constant
                 } else {
-                    let result = if self.lex_state == LexState::EXPR_FNAME
-                        && self.char(0) == Some('=')
-                        && self.char(1) != Some('~')
-                        && self.char(1) != Some('>')
-                        && (self.char(1) != Some('=') || self.char(2) == Some('>'))
-                    {
-                        ident.push(self.next_char().unwrap());
-                        IdentifierType::AssignmentLike
-                    } else if ident.chars().nth(0).unwrap().is_ascii_uppercase() {
-                        IdentifierType::Constant
+                    IdentifierType::Identifier
+                };
+                // Check for a label, which may be named after a keyword (e.g. `if:`) or end in `?`
+                // or `!` (e.g. `a?:`)
+                if self.is_label_possible() && self.is_label_suffix(0) {
+                    self.next_char();
+                    self.lex_state = LexState::EXPR_ARG | LexState::EXPR_LABELED;
+                    return Ok((start_pos, Token::LabelTag { value: ident }, self.get_pos()));
+                }
+                // Check for a keyword
+                if self.state() != LexState::EXPR_DOT && self.state() != LexState::EXPR_FNAME {
+                    // parse.y:5749
+                    if let Some(keyword) = get_keyword(&ident) {
+                        self.track_keyword(&keyword.token, &start_pos)?;
+                        let token = self.lex_keyword(keyword);
+                        self.track_block_keyword(&token);
+                        return Ok((start_pos, token, self.get_pos()));
+                    }
+                }
+                // Check for a block parameter, which is a variable
+                if self.state() != LexState::EXPR_DOT && self.state() != LexState::EXPR_FNAME {
+                    if ident == "it" && self.is_assignment_ahead() {
+                        self.assign_it();
+                    }
+                    let token = if self.is_numbered_parameter(&ident) {
+                        Some(Token::NumberedParameter {
+                            value: ident[1..].parse().unwrap(),
+                        })
+                    } else if ident == "it" && self.is_implicit_parameter() {
+                        Some(Token::ImplicitParameter)
                     } else {
-                        IdentifierType::Identifier
+                        None
                     };
-                    // Check for a label
-                    if self.is_label_possible() && self.is_label_suffix(0) {
+                    if let Some(token) = token {
                         self.lex_state = LexState::EXPR_END;
-                        return Ok((
-                            start_pos,
-                            Token::Identifier { value: ident },
-                            self.get_pos(),
-                        ));
-                    }
-                    // Check for a keyword
-                    if self.lex_state != LexState::EXPR_DOT {
-                        // TODO: parse.y:5749
-                        todo!()
+                        return Ok((start_pos, token, self.get_pos()));
                     }
-                    // Update the lexer's state
-                    self.lex_state =
-                        if self.is_beg() || self.lex_state == LexState::EXPR_DOT || self.is_arg() {
-                            if self.prev_command_state {
-                                LexState::EXPR_CMDARG
-                            } else {
-                                LexState::EXPR_ARG
-                            }
-                        } else if self.lex_state == LexState::EXPR_FNAME {
-                            LexState::EXPR_ENDFN
-                        } else {
-                            LexState::EXPR_END
-                        };
-                    // TODO: parse.y:5807
-                    result
                 }
+                // Update the lexer's state
+                self.lex_state =
+                    if self.is_beg() || self.state() == LexState::EXPR_DOT || self.is_arg() {
+                        if self.prev_command_state {
+                            LexState::EXPR_CMDARG
+                        } else {
+                            LexState::EXPR_ARG
+                        }
+                    } else if self.state() == LexState::EXPR_FNAME {
+                        LexState::EXPR_ENDFN
+                    } else {
+                        LexState::EXPR_END
+                    };
+                // TODO: parse.y:5807
+                result
             }
         };
 
         // parse.y:5679
+        let token = match token_type {
+            IdentifierType::Global => Token::GlobalVariable { value: ident },
+            IdentifierType::Instance => Token::InstanceVariable { value: ident },
+            IdentifierType::Class => Token::ClassVariable { value: ident },
+            IdentifierType::MethodOnly => Token::FunctionIdentifier { value: ident },
+            IdentifierType::AssignmentLike => {
+                Token::AssignmentLikeMethodIdentifier { value: ident }
+            }
+            IdentifierType::Constant => Token::Constant { value: ident },
+            IdentifierType::Identifier => Token::Identifier { value: ident },
+        };
+        Ok((start_pos, token, self.get_pos()))
+    }
+
+    /// Returns the token for a reserved word, updating the lexer's state
+    ///
+    /// Keywords are emitted as their modifier variant unless they begin an expression, and `do`
+    /// is resolved to the construct that it belongs to.
+    fn lex_keyword(&mut self, keyword: Keyword) -> Token<'src> {
+        let state = self.state();
+        // A keyword following a label begins the label's value (e.g. `{a: if x then y end}`)
+        let labeled = self.is_labeled();
+        self.lex_state = keyword.state;
+        if self.state() == LexState::EXPR_BEG {
+            self.command_state = true;
+        }
+        if keyword.token == Token::KwDo {
+            if self.lambda_nesting_levels.last() == Some(&self.nesting_level) {
+                self.lambda_nesting_levels.pop();
+                self.nesting_level -= 1;
+                return Token::KwDoForLambda;
+            }
+            if self.is_cond_active() {
+                self.cond_stack.pop();
+                return Token::KwDoForCondition;
+            }
+            if self.is_cmdarg_active() && state != LexState::EXPR_CMDARG {
+                self.end_command_args();
+                return Token::KwDoForBlock;
+            }
+            if state == LexState::EXPR_ENDARG || state == LexState::EXPR_BEG || labeled {
+                return Token::KwDoForBlock;
+            }
+            return Token::KwDo;
+        }
+        if labeled
+            || matches!(
+                state,
+                LexState::EXPR_BEG | LexState::EXPR_VALUE | LexState::EXPR_CLASS
+            )
+        {
+            match keyword.token {
+                Token::KwWhile | Token::KwUntil | Token::KwFor => self.cond_stack.push(true),
+                _ => {}
+            }
+            return keyword.token;
+        }
+        if keyword.token != keyword.modifier {
+            self.lex_state = LexState::EXPR_BEG;
+        }
+        keyword.modifier
+    }
+
+    /// Lexes a global variable, including special variables and regexp back-references
+    pub(crate) fn lex_global_variable(&mut self) -> LexResult<'src> {
+        let start_pos = self.get_pos();
+        let last_state = self.lex_state;
+        self.lex_state = LexState::EXPR_END;
+        match self.char(1) {
+            // Named globals (including `$_` and `$0`)
+            Some(c) if Self::is_identchar(c) && (!c.is_ascii_digit() || c == '0') => {
+                self.lex_identifier("$")
+            }
+            // Punctuation globals
+            Some(c) if Self::is_global_name_punct(c) && !matches!(c, '&' | '`' | '\'' | '+') => {
+                self.emit_chars_as(|value| Token::GlobalVariable { value }, 2)
+            }
+            // Command-line option globals
+            Some('-') => match self.char(2) {
+                Some(c) if Self::is_identchar(c) => {
+                    self.emit_chars_as(|value| Token::GlobalVariable { value }, 3)
+                }
+                _ => Err(Self::invalid_global_variable("$-", start_pos)),
+            },
+            // Back-references
+            Some('&') | Some('`') | Some('\'') | Some('+') => {
+                if last_state == LexState::EXPR_FNAME {
+                    self.emit_chars_as(|value| Token::GlobalVariable { value }, 2)
+                } else {
+                    self.emit_chars_as(|value| Token::RegexBackRef { value }, 2)
+                }
+            }
+            // Nth-references
+            Some(c) if c.is_ascii_digit() => {
+                self.next_char();
+                while let Some(c) = self.char(0) {
+                    if !c.is_ascii_digit() {
+                        break;
+                    }
+                    self.next_char();
+                }
+                let value = self.slice_from(&start_pos);
+                let token = if last_state == LexState::EXPR_FNAME {
+                    Token::GlobalVariable { value }
+                } else {
+                    if value[1..].parse::<i32>().is_err() {
+                        self.warn_at(
+                            WarningKind::NthRefTooBig,
+                            &format!("`{}' is too big for a number variable, always nil", value),
+                            start_pos.clone(),
+                        );
+                    }
+                    Token::RegexNthRef { value }
+                };
+                Ok((start_pos, token, self.get_pos()))
+            }
+            Some(c) if !Self::is_space(c) => {
+                Err(Self::invalid_global_variable(&format!("${}", c), start_pos))
+            }
+            _ => Err(LexicalError {
+                message: "`$' without identifiers is not allowed as a global variable name"
+                    .to_owned(),
+                location: start_pos,
+            }),
+        }
+    }
 
-        todo!()
+    /// Returns an error for a global variable with an invalid name
+    fn invalid_global_variable(name: &str, location: Location) -> LexicalError {
+        LexicalError {
+            message: format!("`{}' is not allowed as a global variable name", name),
+            location,
+        }
     }
 
     // Lexes a named identifier
-    // fn lex_identifier(&mut self) -> LexResult {
+    // fn lex_identifier(&mut self) -> LexResult<'src> {
     //     let mut name = String::new();
     //     let start_pos = self.get_pos();
     //
@@ -0,0 +1,415 @@
+use super::blocks::Block;
+use super::heredocs::LineIndent;
+use super::strings::StringTerm;
+use super::{LexState, Lexer, Location, MagicComments, RubyVersion, SpannedToken, Token};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::ops::Range;
+
+/// A snapshot of the lexer's state, from which lexing can be resumed (see `Lexer::checkpoint`)
+///
+/// Checkpoints compare equal when lexing the same source from either of them produces the same
+/// tokens. The configuration of the lexer, such as error recovery, and the errors and warnings
+/// it has collected are not part of its state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint<'src> {
+    location: Location,
+    nesting_level: usize,
+    prev_lex_state: LexState,
+    lex_state: LexState,
+    parsing_heredoc: bool,
+    pending_heredocs: VecDeque<StringTerm>,
+    heredoc_indents: Option<Vec<LineIndent>>,
+    pending_tokens: VecDeque<SpannedToken<'src>>,
+    lex_strterm: Vec<StringTerm>,
+    seen_whitespace: bool,
+    prev_command_state: bool,
+    command_state: bool,
+    cond_stack: Vec<bool>,
+    cmdarg_stack: Vec<bool>,
+    lambda_nesting_levels: Vec<usize>,
+    lambda_parameters: Vec<bool>,
+    blocks: Vec<Block>,
+    keyword_depth: usize,
+    it_assigned: bool,
+    magic_comments: MagicComments,
+    last_token_line: Option<usize>,
+    shebang: bool,
+    method_definition: Option<usize>,
+    arrow_allowed: bool,
+}
+
+impl<'src> Checkpoint<'src> {
+    /// Returns the location at which lexing resumes from the checkpoint
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    /// Moves the locations held by the checkpoint to where they are found after an edit
+    fn shift(&mut self, shift: &Shift) {
+        shift.location(&mut self.location);
+        if let Some(line) = self.last_token_line.as_mut() {
+            shift.line(line);
+        }
+        for strterm in self.lex_strterm.iter_mut() {
+            shift.location(&mut strterm.start);
+        }
+        for strterm in self.pending_heredocs.iter_mut() {
+            shift.location(&mut strterm.start);
+        }
+        for token in self.pending_tokens.iter_mut() {
+            shift.token(token);
+        }
+    }
+}
+
+impl<'src> Lexer<'src> {
+    /// Returns a checkpoint holding the lexer's current state
+    pub fn checkpoint(&self) -> Checkpoint<'src> {
+        Checkpoint {
+            location: self.location.clone(),
+            nesting_level: self.nesting_level,
+            prev_lex_state: self.prev_lex_state,
+            lex_state: self.lex_state,
+            parsing_heredoc: self.parsing_heredoc,
+            pending_heredocs: self.pending_heredocs.clone(),
+            heredoc_indents: self.heredoc_indents.clone(),
+            pending_tokens: self.pending_tokens.clone(),
+            lex_strterm: self.lex_strterm.clone(),
+            seen_whitespace: self.seen_whitespace,
+            prev_command_state: self.prev_command_state,
+            command_state: self.command_state,
+            cond_stack: self.cond_stack.clone(),
+            cmdarg_stack: self.cmdarg_stack.clone(),
+            lambda_nesting_levels: self.lambda_nesting_levels.clone(),
+            lambda_parameters: self.lambda_parameters.clone(),
+            blocks: self.blocks.clone(),
+            keyword_depth: self.keyword_depth,
+            it_assigned: self.it_assigned,
+            magic_comments: self.magic_comments.clone(),
+            last_token_line: self.last_token_line,
+            shebang: self.shebang,
+            method_definition: self.method_definition,
+            arrow_allowed: self.arrow_allowed,
+        }
+    }
+
+    /// Restores the state held by the checkpoint, so that lexing resumes from its location
+    ///
+    /// The checkpoint may have been taken by a lexer for another source, as long as both sources
+    /// are identical up to the checkpoint's location.
+    pub fn restore(&mut self, checkpoint: Checkpoint<'src>) {
+        self.location = checkpoint.location;
+        self.nesting_level = checkpoint.nesting_level;
+        self.prev_lex_state = checkpoint.prev_lex_state;
+        self.lex_state = checkpoint.lex_state;
+        self.parsing_heredoc = checkpoint.parsing_heredoc;
+        self.pending_heredocs = checkpoint.pending_heredocs;
+        self.heredoc_indents = checkpoint.heredoc_indents;
+        self.pending_tokens = checkpoint.pending_tokens;
+        self.lex_strterm = checkpoint.lex_strterm;
+        self.seen_whitespace = checkpoint.seen_whitespace;
+        self.prev_command_state = checkpoint.prev_command_state;
+        self.command_state = checkpoint.command_state;
+        self.cond_stack = checkpoint.cond_stack;
+        self.cmdarg_stack = checkpoint.cmdarg_stack;
+        self.lambda_nesting_levels = checkpoint.lambda_nesting_levels;
+        self.lambda_parameters = checkpoint.lambda_parameters;
+        self.blocks = checkpoint.blocks;
+        self.keyword_depth = checkpoint.keyword_depth;
+        self.it_assigned = checkpoint.it_assigned;
+        self.magic_comments = checkpoint.magic_comments;
+        self.last_token_line = checkpoint.last_token_line;
+        self.shebang = checkpoint.shebang;
+        self.method_definition = checkpoint.method_definition;
+        self.arrow_allowed = checkpoint.arrow_allowed;
+    }
+}
+
+/// Describes how the tokens of an `IncrementalLexer` changed after an edit
+///
+/// The `removed` tokens starting at index `start` were replaced by the `inserted` tokens now
+/// found at `tokens()[start..start + inserted]`. The tokens following them are unchanged, apart
+/// from their locations.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TokenDelta {
+    pub start: usize,
+    pub removed: usize,
+    pub inserted: usize,
+}
+
+/// Holds the tokens of a source, which are updated by re-lexing only the affected part of the
+/// source after each edit
+///
+/// Checkpoints are kept at the start of each line. An edit is lexed from the last checkpoint
+/// preceding it until the lexer reaches a line start beyond the edit in the same state as before
+/// the edit, from which the old tokens are reused. Errors are recovered from, so malformed input
+/// is lexed as `Token::Error` (see `Lexer::with_error_recovery`).
+pub struct IncrementalLexer {
+    source: String,
+    ruby_version: RubyVersion,
+    tokens: Vec<SpannedToken<'static>>,
+    /// The checkpoints taken at the start of lines, along with the index of the next token
+    checkpoints: Vec<(usize, Checkpoint<'static>)>,
+}
+
+impl IncrementalLexer {
+    /// Lexes the source
+    pub fn new(source: String) -> Self {
+        Self::with_ruby_version(source, RubyVersion::default())
+    }
+
+    /// Lexes the source with the syntax of the given version of Ruby (see
+    /// `Lexer::with_ruby_version`)
+    pub fn with_ruby_version(source: String, ruby_version: RubyVersion) -> Self {
+        let mut lexer = Self::lexer(&source, ruby_version);
+        let mut tokens = Vec::new();
+        let mut checkpoints = Vec::new();
+        Self::lex(&mut lexer, 0, &mut tokens, &mut checkpoints, |_| None::<()>);
+        IncrementalLexer {
+            source,
+            ruby_version,
+            tokens,
+            checkpoints,
+        }
+    }
+
+    /// Returns the current source
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the tokens of the current source, without the final `Token::EndOfFile`
+    pub fn tokens(&self) -> &[SpannedToken<'static>] {
+        &self.tokens
+    }
+
+    /// Replaces the given byte range of the source with the text, re-lexing the affected tokens
+    ///
+    /// # Panics
+    ///  * Panics if the range is out of bounds or doesn't lie on char boundaries.
+    pub fn edit(&mut self, range: Range<usize>, text: &str) -> TokenDelta {
+        let removed_text = &self.source[range.clone()];
+        let (old_line, old_cols) = Shift::position(&self.source[..range.end]);
+        let (_, new_cols) = Shift::position(&[&self.source[..range.start], text].concat());
+        let shift = Shift {
+            end: range.end,
+            line: old_line,
+            lines: text.matches('\n').count() as isize
+                - removed_text.matches('\n').count() as isize,
+            bytes: text.len() as isize - removed_text.len() as isize,
+            chars: text.chars().count() as isize - removed_text.chars().count() as isize,
+            cols: [
+                new_cols[0] as isize - old_cols[0] as isize,
+                new_cols[1] as isize - old_cols[1] as isize,
+                new_cols[2] as isize - old_cols[2] as isize,
+            ],
+        };
+        self.source.replace_range(range.clone(), text);
+
+        // Resume lexing from the last checkpoint preceding the edit, or rather from the start of
+        // the line of code before it, whose newline looks past any blank or comment-only lines
+        // for a leading `.` or `&.`
+        let mut resume = self
+            .checkpoints
+            .iter()
+            .rposition(|(_, checkpoint)| checkpoint.location.offset < range.start)
+            .unwrap_or(0);
+        while resume > 0 {
+            resume -= 1;
+            let line = &self.tokens[self.checkpoints[resume].0..self.checkpoints[resume + 1].0];
+            let code = line.iter().any(|(_, token, _)| {
+                !matches!(
+                    token,
+                    Token::Whitespace
+                        | Token::Comment { .. }
+                        | Token::Newline
+                        | Token::LineTerminator
+                )
+            });
+            if code {
+                break;
+            }
+        }
+        let old_checkpoints = self.checkpoints.split_off(resume);
+        let (start, checkpoint) = old_checkpoints[0].clone();
+        let mut lexer = Self::lexer(&self.source, self.ruby_version);
+        lexer.restore(checkpoint);
+
+        // Lex until the state converges with a checkpoint following the edit
+        let edit_end = range.start + text.len();
+        let mut tokens = Vec::new();
+        let converged = Self::lex(
+            &mut lexer,
+            start,
+            &mut tokens,
+            &mut self.checkpoints,
+            |checkpoint| {
+                let offset = checkpoint.location.offset;
+                if offset < edit_end {
+                    return None;
+                }
+                let old_offset = (offset as isize - shift.bytes) as usize;
+                let index = old_checkpoints
+                    .binary_search_by_key(&old_offset, |(_, old)| old.location.offset)
+                    .ok()?;
+                let (token_index, mut old) = old_checkpoints[index].clone();
+                old.shift(&shift);
+                if old == *checkpoint {
+                    Some((token_index, index))
+                } else {
+                    None
+                }
+            },
+        );
+
+        // Reuse the old tokens and checkpoints following the point of convergence
+        let end = match converged {
+            Some((end, index)) => {
+                for (token_index, checkpoint) in &old_checkpoints[index..] {
+                    let mut checkpoint = checkpoint.clone();
+                    checkpoint.shift(&shift);
+                    let token_index = token_index - end + start + tokens.len();
+                    self.checkpoints.push((token_index, checkpoint));
+                }
+                end
+            }
+            None => self.tokens.len(),
+        };
+        for token in self.tokens[end..].iter_mut() {
+            shift.token(token);
+        }
+
+        // Leave out the re-lexed tokens that are unchanged from the delta
+        let old_tokens = &self.tokens[start..end];
+        let prefix = old_tokens
+            .iter()
+            .zip(tokens.iter())
+            .take_while(|(old, new)| old == new)
+            .count();
+        let suffix = old_tokens[prefix..]
+            .iter()
+            .rev()
+            .zip(tokens[prefix..].iter().rev())
+            .take_while(|(old, new)| {
+                let mut old = (*old).clone();
+                shift.token(&mut old);
+                old == **new
+            })
+            .count();
+        let delta = TokenDelta {
+            start: start + prefix,
+            removed: old_tokens.len() - prefix - suffix,
+            inserted: tokens.len() - prefix - suffix,
+        };
+        self.tokens.splice(start..end, tokens);
+        delta
+    }
+
+    /// Returns a lexer for the source, which recovers from errors
+    fn lexer(source: &str, ruby_version: RubyVersion) -> Lexer<'static> {
+        Lexer::with_source(Cow::Owned(source.to_owned()))
+            .with_error_recovery()
+            .with_ruby_version(ruby_version)
+    }
+
+    /// Lexes tokens until the end of the source, taking a checkpoint at the start of each line
+    ///
+    /// `start` is the index of the first lexed token within the whole token stream. Stops early
+    /// once `converged` returns a value for a checkpoint, which is then returned.
+    fn lex<F, T>(
+        lexer: &mut Lexer<'static>,
+        start: usize,
+        tokens: &mut Vec<SpannedToken<'static>>,
+        checkpoints: &mut Vec<(usize, Checkpoint<'static>)>,
+        mut converged: F,
+    ) -> Option<T>
+    where
+        F: FnMut(&Checkpoint<'static>) -> Option<T>,
+    {
+        loop {
+            let offset = lexer.location.offset;
+            let line_start = lexer.location.col() == 1
+                && lexer.pending_tokens.is_empty()
+                && checkpoints
+                    .last()
+                    .is_none_or(|(_, last)| last.location.offset < offset);
+            if line_start {
+                let checkpoint = lexer.checkpoint();
+                if let Some(result) = converged(&checkpoint) {
+                    return Some(result);
+                }
+                checkpoints.push((start + tokens.len(), checkpoint));
+            }
+            match lexer.emit_token() {
+                Ok((_, Token::EndOfFile, _)) => return None,
+                Ok(token) => tokens.push(token),
+                Err(_) => unreachable!("lexical errors are recovered from"),
+            }
+        }
+    }
+}
+
+/// Maps the locations following an edit from the old source onto the new source
+struct Shift {
+    /// The offset at which the edit ended in the old source
+    end: usize,
+    /// The line on which the edit ended in the old source
+    line: usize,
+    /// The change in the number of lines
+    lines: isize,
+    /// The change in the number of bytes
+    bytes: isize,
+    /// The change in the number of chars
+    chars: isize,
+    /// The change in the char, byte and UTF-16 columns of the rest of the line on which the
+    /// edit ended
+    cols: [isize; 3],
+}
+
+impl Shift {
+    /// Returns the line and the char, byte and UTF-16 columns found at the end of the text
+    fn position(text: &str) -> (usize, [usize; 3]) {
+        let line_start = text.rfind('\n').map_or(0, |i| i + 1);
+        let line = &text[line_start..];
+        let cols = [
+            line.chars().count() + 1,
+            line.len() + 1,
+            line.encode_utf16().count() + 1,
+        ];
+        (text.matches('\n').count() + 1, cols)
+    }
+
+    /// Moves a location at which something starts, unless it precedes the edit
+    fn location(&self, location: &mut Location) {
+        if location.offset >= self.end {
+            self.apply(location);
+        }
+    }
+
+    fn apply(&self, location: &mut Location) {
+        if location.line == self.line {
+            location.col = (location.col as isize + self.cols[0]) as usize;
+            location.byte_col = (location.byte_col as isize + self.cols[1]) as usize;
+            location.utf16_col = (location.utf16_col as isize + self.cols[2]) as usize;
+        }
+        location.line = (location.line as isize + self.lines) as usize;
+        location.offset = (location.offset as isize + self.bytes) as usize;
+        location.char_offset = (location.char_offset as isize + self.chars) as usize;
+    }
+
+    fn line(&self, line: &mut usize) {
+        if *line >= self.line {
+            *line = (*line as isize + self.lines) as usize;
+        }
+    }
+
+    /// Moves the span of a token, whose end is only moved along with its start if the token ends
+    /// where the edit ended
+    fn token(&self, token: &mut SpannedToken) {
+        if token.0.offset >= self.end || token.2.offset > self.end {
+            self.apply(&mut token.2);
+        }
+        self.location(&mut token.0);
+    }
+}
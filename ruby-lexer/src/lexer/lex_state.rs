@@ -1,4 +1,4 @@
-use super::Lexer;
+use super::{Lexer, WarningKind};
 
 bitflags! {
     #[derive(Default)]
@@ -24,43 +24,112 @@ bitflags! {
     }
 }
 
-impl<T> Lexer<T>
-where
-    T: Iterator<Item = char>,
-{
+impl<'src> Lexer<'src> {
+    /// Returns the lexer's state without the `EXPR_LABEL` and `EXPR_LABELED` flag bits
+    pub(crate) fn state(&self) -> LexState {
+        self.lex_state - (LexState::EXPR_LABEL | LexState::EXPR_LABELED)
+    }
+    /// Checks if the last token was a label, which is followed by a value like the beginning
+    /// of an expression
+    pub(crate) fn is_labeled(&self) -> bool {
+        self.lex_state.contains(LexState::EXPR_LABELED)
+    }
     pub(crate) fn is_arg(&self) -> bool {
-        match self.lex_state {
-            LexState::EXPR_ARG | LexState::EXPR_CMDARG => true,
-            _ => false,
+        if self.is_labeled() {
+            return false;
         }
+        matches!(self.state(), LexState::EXPR_ARG | LexState::EXPR_CMDARG)
     }
     pub(crate) fn is_end(&self) -> bool {
-        match self.lex_state {
-            LexState::EXPR_END | LexState::EXPR_ENDARG | LexState::EXPR_ENDFN => true,
-            _ => false,
-        }
+        matches!(
+            self.state(),
+            LexState::EXPR_END | LexState::EXPR_ENDARG | LexState::EXPR_ENDFN
+        )
     }
     pub(crate) fn is_beg(&self) -> bool {
-        match self.lex_state {
-            LexState::EXPR_BEG
-            | LexState::EXPR_MID
-            | LexState::EXPR_VALUE
-            | LexState::EXPR_CLASS => true,
-            _ => false,
+        if self.is_labeled() {
+            return true;
         }
+        matches!(
+            self.state(),
+            LexState::EXPR_BEG | LexState::EXPR_MID | LexState::EXPR_VALUE | LexState::EXPR_CLASS
+        )
+    }
+    pub(crate) fn is_after_operator(&self) -> bool {
+        matches!(self.state(), LexState::EXPR_FNAME | LexState::EXPR_DOT)
     }
     pub(crate) fn is_spcarg(&self, c: char) -> bool {
         self.is_arg() && self.seen_whitespace && !Self::is_whitespace(c)
     }
+    /// Checks if an upcoming `+` or `-` is a unary operator, warning if it is ambiguous
+    pub(crate) fn is_unary_operator(&mut self) -> bool {
+        if self.is_beg() {
+            return true;
+        }
+        match self.char(1) {
+            Some(c) if self.is_spcarg(c) => {
+                let c = self.char(0).unwrap();
+                self.warn(
+                    WarningKind::AmbiguousFirstArgument,
+                    &format!(
+                        "ambiguous first argument; put parentheses or a space even after `{}' operator",
+                        c
+                    ),
+                );
+                true
+            }
+            _ => false,
+        }
+    }
+    /// Checks if an identifier or quoted string may be a label (`key:`), which it may be at the
+    /// start of a hash element or argument, or after a method definition's name
     pub(crate) fn is_label_possible(&self) -> bool {
-        (self.lex_state == LexState::EXPR_BEG && !self.prev_command_state) || self.is_arg()
+        let state =
+            self.lex_state.contains(LexState::EXPR_LABEL) || self.state() == LexState::EXPR_ENDFN;
+        (state && !self.prev_command_state) || self.is_arg()
     }
+    /// Checks if the character at the given offset is the `:` of a label, rather than the
+    /// start of a `::`
     pub(crate) fn is_label_suffix(&self, i: usize) -> bool {
-        self.char(i) == Some(':') || self.char(i + 1) != Some(':')
+        self.char(i) == Some(':') && self.char(i + 1) != Some(':')
+    }
+    /// Checks if the innermost condition is that of a `while`, `until` or `for` loop
+    pub(crate) fn is_cond_active(&self) -> bool {
+        self.cond_stack.last() == Some(&true)
+    }
+    /// Checks if the innermost command arguments belong to a command call without parentheses
+    pub(crate) fn is_cmdarg_active(&self) -> bool {
+        self.cmdarg_stack.last() == Some(&true)
+    }
+    /// Marks the start of a bracket or interpolation, within which loop conditions and command
+    /// arguments are suspended
+    pub(crate) fn push_nesting(&mut self) {
+        self.cond_stack.push(false);
+        self.cmdarg_stack.push(false);
+    }
+    /// Marks the end of a bracket or interpolation, closing any conditions and command
+    /// arguments started within it
+    pub(crate) fn pop_nesting(&mut self) {
+        self.end_statement();
+        self.cond_stack.pop();
+        self.cmdarg_stack.pop();
+    }
+    /// Closes the command arguments of any command calls without parentheses
+    pub(crate) fn end_command_args(&mut self) {
+        while self.is_cmdarg_active() {
+            self.cmdarg_stack.pop();
+        }
+    }
+    /// Closes any conditions and command arguments at the end of a statement
+    pub(crate) fn end_statement(&mut self) {
+        while self.is_cond_active() {
+            self.cond_stack.pop();
+        }
+        self.end_command_args();
     }
     /// Updates the lexer's state after parsing operators and punctuators
     pub(crate) fn set_lexer_newline_state(&mut self) {
-        if self.lex_state == LexState::EXPR_FNAME || self.lex_state == LexState::EXPR_DOT {
+        if self.state() == LexState::EXPR_FNAME || self.state() == LexState::EXPR_DOT {
             self.lex_state = LexState::EXPR_ARG;
         } else {
             self.lex_state = LexState::EXPR_BEG;
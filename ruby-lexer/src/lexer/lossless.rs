@@ -0,0 +1,187 @@
+use super::{LexResult, Lexer, LexicalError, Location, Token};
+use crate::{LosslessToken, Trivia, TriviaKind};
+
+/// Iterates over the tokens of a lexer in lossless mode
+pub struct LosslessLexer<'src> {
+    lexer: Lexer<'src>,
+    /// The result lexed after the trailing trivia of the previous token
+    peeked: Option<LexResult<'src>>,
+    /// Whether the end of the file or a lexical error was reached
+    finished: bool,
+    /// The offset at which the last token or trivia ended
+    offset: usize,
+}
+
+impl<'src> Lexer<'src> {
+    /// Switches the lexer to lossless mode, in which each token carries its exact source text
+    /// and the trivia surrounding it
+    ///
+    /// Whitespace, line continuations, comments, embedded documents and insignificant newlines
+    /// are attached to the neighbouring tokens as trivia rather than produced as tokens. The last
+    /// token is a `Token::EndOfFile` holding the trivia at the end of the source. A lexer created
+    /// by `from_bytes` reproduces the decoded source, without any byte order mark.
+    pub fn into_lossless(self) -> LosslessLexer<'src> {
+        LosslessLexer {
+            lexer: self,
+            peeked: None,
+            finished: false,
+            offset: 0,
+        }
+    }
+}
+
+impl<'src> LosslessLexer<'src> {
+    /// Returns the underlying lexer
+    pub fn lexer(&self) -> &Lexer<'src> {
+        &self.lexer
+    }
+
+    fn next_result(&mut self) -> LexResult<'src> {
+        match self.peeked.take() {
+            Some(result) => result,
+            None => self.lexer.emit_token(),
+        }
+    }
+
+    /// Returns the kind of trivia represented by the token, if any
+    fn trivia_kind(token: &Token) -> Option<TriviaKind> {
+        match token {
+            Token::Whitespace => Some(TriviaKind::Whitespace),
+            Token::Newline => Some(TriviaKind::Newline),
+            Token::Comment { .. } => Some(TriviaKind::Comment),
+            Token::EmbeddedDocument { .. } => Some(TriviaKind::EmbeddedDocument),
+            _ => None,
+        }
+    }
+
+    /// Appends the trivia between the given locations, along with the whitespace separating it
+    /// from the previous token or trivia
+    ///
+    /// The whitespace separating the elements of a `%w`, `%W`, `%i` or `%I` list isn't lexed as a
+    /// token, so it is only found as a gap between tokens.
+    fn push_trivia(
+        &mut self,
+        trivia: &mut Vec<Trivia<'src>>,
+        kind: Option<TriviaKind>,
+        start: &Location,
+        end: &Location,
+    ) {
+        if self.offset < start.offset() {
+            self.push_whitespace(trivia, self.offset, start.offset());
+        }
+        match kind {
+            Some(TriviaKind::Whitespace) => {
+                self.push_whitespace(trivia, start.offset(), end.offset())
+            }
+            Some(kind) => trivia.push(Trivia {
+                kind,
+                text: self.lexer.slice(start.offset(), end.offset()),
+            }),
+            None => {}
+        }
+        self.offset = end.offset();
+    }
+
+    /// Appends the whitespace between the given offsets, split into runs of whitespace, line
+    /// continuations and newlines
+    fn push_whitespace(&self, trivia: &mut Vec<Trivia<'src>>, start: usize, end: usize) {
+        let text = &self.lexer.source[start..end];
+        let mut run_start = 0;
+        let mut index = 0;
+        while index < text.len() {
+            let rest = &text[index..];
+            let (kind, len) = if rest.starts_with("\\\r\n") {
+                (TriviaKind::LineContinuation, 3)
+            } else if rest.starts_with("\\\n") {
+                (TriviaKind::LineContinuation, 2)
+            } else if rest.starts_with("\r\n") {
+                (TriviaKind::Newline, 2)
+            } else if rest.starts_with('\n') {
+                (TriviaKind::Newline, 1)
+            } else {
+                index += rest.chars().next().unwrap().len_utf8();
+                continue;
+            };
+            if run_start < index {
+                trivia.push(Trivia {
+                    kind: TriviaKind::Whitespace,
+                    text: self.lexer.slice(start + run_start, start + index),
+                });
+            }
+            trivia.push(Trivia {
+                kind,
+                text: self.lexer.slice(start + index, start + index + len),
+            });
+            index += len;
+            run_start = index;
+        }
+        if run_start < text.len() {
+            trivia.push(Trivia {
+                kind: TriviaKind::Whitespace,
+                text: self.lexer.slice(start + run_start, end),
+            });
+        }
+    }
+}
+
+impl<'src> Iterator for LosslessLexer<'src> {
+    type Item = Result<LosslessToken<'src>, LexicalError>;
+
+    /// Produces the next token along with its trivia, unless the end of the file was reached
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        // Collect the leading trivia
+        let mut leading_trivia = Vec::new();
+        let (start, token, end) = loop {
+            match self.next_result() {
+                Ok((start, token, end)) => {
+                    let kind = Self::trivia_kind(&token);
+                    self.push_trivia(&mut leading_trivia, kind, &start, &end);
+                    if kind.is_none() {
+                        break (start, token, end);
+                    }
+                }
+                Err(error) => {
+                    self.finished = true;
+                    return Some(Err(error));
+                }
+            }
+        };
+
+        // Collect the trailing trivia on the token's line
+        let mut trailing_trivia = Vec::new();
+        if token == Token::EndOfFile {
+            self.finished = true;
+        } else {
+            loop {
+                match self.lexer.emit_token() {
+                    Ok((start, token, end)) => match Self::trivia_kind(&token) {
+                        kind @ Some(TriviaKind::Whitespace) | kind @ Some(TriviaKind::Comment) => {
+                            self.push_trivia(&mut trailing_trivia, kind, &start, &end)
+                        }
+                        _ => {
+                            self.peeked = Some(Ok((start, token, end)));
+                            break;
+                        }
+                    },
+                    result => {
+                        self.peeked = Some(result);
+                        break;
+                    }
+                }
+            }
+        }
+
+        Some(Ok(LosslessToken {
+            text: self.lexer.slice(start.offset(), end.offset()),
+            token,
+            start,
+            end,
+            leading_trivia,
+            trailing_trivia,
+        }))
+    }
+}
@@ -1,14 +1,11 @@
-use super::{LexResult, LexState, Lexer, LexicalError, Token};
-//use num_bigint::BigInt;
+use super::{get_keyword, LexResult, LexState, Lexer, LexicalError, Token};
 use log::trace;
+use num_bigint::BigInt;
+use num_integer::Integer;
 
-impl<T> Lexer<T>
-where
-    T: Iterator<Item = char>,
-{
+impl<'src> Lexer<'src> {
     /// Lexes and returns a numeric token
-    pub(crate) fn lex_number(&mut self) -> LexResult {
-
+    pub(crate) fn lex_number(&mut self) -> LexResult<'src> {
         // parse.y:5052
         let mut seen_point = false;
         let mut seen_e = false;
@@ -61,7 +58,7 @@ where
                     '8'..='9' => {
                         return Err(LexicalError {
                             message: "Invalid octal digit".to_owned(),
-                            location: self.get_pos()
+                            location: self.get_pos(),
                         })
                     }
                     '.' | 'e' | 'E' => {
@@ -72,19 +69,29 @@ where
                     }
                     _ => {
                         // Only character seen was a zero
-                        trace!("End: found zero");
-                        return Ok((start, Token::Integer { value: 0 }, self.get_pos()));
+                        trace!("Found zero");
+                        non_digit = None;
+                        tok.push('0');
+                        10
                     }
                 };
             } else {
                 // End of input
-                trace!("End: found zero");
-                return Ok((start, Token::Integer { value: 0 }, self.get_pos()));
+                trace!("Found zero");
+                tok.push('0');
             }
         }
 
         trace!("Radix is: {}", radix);
 
+        // A radix prefix must be followed by at least one digit
+        if non_digit != Some('_') && non_digit.is_some() && !Self::is_digit(self.char(0), radix) {
+            return Err(LexicalError {
+                message: "numeric literal without digits".to_owned(),
+                location: self.get_pos(),
+            });
+        }
+
         // TODO: 5192 and beyond
         while let Some(c) = self.char(0) {
             trace!("Next char is: '{}'", c);
@@ -92,7 +99,9 @@ where
             // Handle digits
             if c.is_digit(radix) {
                 trace!("Found digit: {}", c);
-                tok.push(self.next_char().unwrap());
+                let digits_start = self.location.offset;
+                self.skip_while(|c| c.is_digit(radix));
+                tok.push_str(&self.source[digits_start..self.location.offset]);
                 non_digit = None;
                 continue;
             }
@@ -147,19 +156,123 @@ where
         if let Some(c) = non_digit {
             return Err(LexicalError {
                 message: format!("trailing '{}' in number", c),
-                location: self.get_pos()
+                location: self.get_pos(),
+            });
+        }
+
+        // Error on a digit that is too large for the radix (e.g. `0b12`)
+        if self.char(0).is_some_and(|c| c.is_ascii_digit()) {
+            let name = match radix {
+                2 => "binary",
+                _ => "octal",
+            };
+            return Err(LexicalError {
+                message: format!("Invalid {} digit", name),
+                location: self.get_pos(),
             });
         }
 
         trace!("Lexed number contains: {:?}", tok);
 
-        // Return a parsed token
-        if seen_e || seen_point {
-            let value = tok.parse::<f64>().unwrap(); // TODO: handle fails
-            return Ok((start, Token::Float { value: value }, self.get_pos()));
+        // Check for rational and imaginary suffixes
+        let (rational, imaginary) = self.read_number_suffix(!seen_e);
+
+        // Build the parsed token
+        let mut token = if seen_e || seen_point {
+            if rational {
+                Self::decimal_rational(&tok)
+            } else {
+                let value = tok.parse::<f64>().unwrap(); // TODO: handle fails
+                Token::Float { value }
+            }
+        } else if rational {
+            Token::Rational {
+                numerator: BigInt::parse_bytes(tok.as_bytes(), radix).unwrap(),
+                denominator: BigInt::from(1),
+            }
         } else {
-            let value = isize::from_str_radix(&tok, radix).unwrap(); // TODO: handle fails
-            return Ok((start, Token::Integer { value: value }, self.get_pos()));
+            // Integers too large for an isize are lexed as a bignum
+            match isize::from_str_radix(&tok, radix) {
+                Ok(value) => Token::Integer { value },
+                Err(_) => Token::BigInteger {
+                    value: BigInt::parse_bytes(tok.as_bytes(), radix).unwrap(),
+                },
+            }
+        };
+        if imaginary {
+            token = Token::Imaginary {
+                value: Box::new(token),
+            };
+        }
+
+        // Error on an identifier that immediately follows the number (e.g. `1e3r`)
+        if self.is_identifier_after_number() {
+            return Err(LexicalError {
+                message: "syntax error, unexpected local variable or method".to_owned(),
+                location: self.get_pos(),
+            });
+        }
+
+        Ok((start, token, self.get_pos()))
+    }
+
+    /// Reads the `r`, `i` or `ri` suffix of a numeric literal, returning whether the number is
+    /// rational and whether it is imaginary
+    ///
+    /// Suffixes followed by other identifier characters are not consumed (parse.y's
+    /// `number_literal_suffix`).
+    fn read_number_suffix(&mut self, allow_rational: bool) -> (bool, bool) {
+        let mut allow_rational = allow_rational;
+        let mut allow_imaginary = true;
+        let mut rational = false;
+        let mut imaginary = false;
+        let mut len = 0;
+        while let Some(c) = self.char(len) {
+            if allow_imaginary && c == 'i' {
+                // A rational suffix may not follow an imaginary suffix
+                imaginary = true;
+                allow_imaginary = false;
+                allow_rational = false;
+            } else if allow_rational && c == 'r' {
+                rational = true;
+                allow_rational = false;
+            } else if !c.is_ascii() || c.is_ascii_alphabetic() || c == '_' {
+                return (false, false);
+            } else {
+                break;
+            }
+            len += 1;
+        }
+        for _ in 0..len {
+            self.next_char();
+        }
+        (rational, imaginary)
+    }
+
+    /// Checks if the upcoming characters are an identifier other than a keyword
+    ///
+    /// Keywords may directly follow a number (e.g. `1if x`).
+    fn is_identifier_after_number(&self) -> bool {
+        let rest = &self.source[self.location.offset..];
+        let len = rest.find(|c| !Self::is_identchar(c)).unwrap_or(rest.len());
+        let ident = &rest[..len];
+        !ident.is_empty() && (!ident.is_ascii() || get_keyword(ident).is_none())
+    }
+
+    /// Returns the exact rational value of a decimal number (e.g. `1.5` as `3/2`)
+    fn decimal_rational(tok: &str) -> Token<'src> {
+        let (integer, fraction) = match tok.find('.') {
+            Some(index) => (&tok[..index], &tok[index + 1..]),
+            None => (tok, ""),
+        };
+        let numerator = format!("{}{}", integer, fraction);
+        let denominator = format!("1{}", "0".repeat(fraction.len()));
+        let numerator = BigInt::parse_bytes(numerator.as_bytes(), 10).unwrap();
+        let denominator = BigInt::parse_bytes(denominator.as_bytes(), 10).unwrap();
+        let gcd = numerator.gcd(&denominator);
+        Token::Rational {
+            numerator: numerator / &gcd,
+            denominator: denominator / &gcd,
         }
     }
 
@@ -0,0 +1,97 @@
+use super::{LexResult, LexState, Lexer, LexicalError, Location, Token};
+
+impl<'src> Lexer<'src> {
+    /// Enables error recovery
+    ///
+    /// Rather than returning a lexical error, a recovering lexer records the error, skips past
+    /// the malformed input and emits a `Token::Error` spanning it. Lexing resumes at the end of
+    /// the string literal containing the error, or otherwise at the end of the line or the
+    /// closing delimiter of the enclosing brackets.
+    pub fn with_error_recovery(mut self) -> Self {
+        self.recover_errors = true;
+        self
+    }
+
+    /// Returns the lexical errors recorded while recovering from errors
+    pub fn errors(&self) -> &[LexicalError] {
+        &self.errors
+    }
+
+    /// Removes and returns the lexical errors recorded while recovering from errors
+    pub fn take_errors(&mut self) -> Vec<LexicalError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /// Records a lexical error and skips the malformed input, which is emitted as an error token
+    pub(crate) fn recover_from_error(
+        &mut self,
+        error: LexicalError,
+        tok_start: Location,
+    ) -> LexResult<'src> {
+        self.errors.push(error);
+        match self.lex_strterm.last() {
+            Some(term) if term.embedded.is_none() => self.skip_string_content(),
+            _ => self.skip_line(),
+        }
+
+        // Always move past at least one character, so that the same error isn't raised again
+        if self.get_pos().offset() == tok_start.offset() && self.next_char().is_none() {
+            // Abandon any unterminated literals at the end of the input
+            self.abandon_literals();
+        }
+
+        self.lex_state = LexState::EXPR_END;
+        Ok((tok_start, Token::Error, self.get_pos()))
+    }
+
+    /// Skips the remaining contents of the string literal being lexed
+    ///
+    /// Heredocs resume at their next line, while other literals are closed.
+    fn skip_string_content(&mut self) {
+        let term = self.lex_strterm.last().unwrap().clone();
+        if term.heredoc.is_some() {
+            while let Some(c) = self.next_char() {
+                if c == '\n' {
+                    break;
+                }
+            }
+            let strterm = self.lex_strterm.last_mut().unwrap();
+            strterm.heredoc.as_mut().unwrap().line_head = true;
+            if self.char(0).is_none() {
+                self.lex_strterm.pop();
+            }
+            return;
+        }
+        let mut nest = 0;
+        while let Some(c) = self.next_char() {
+            if c == '\\' {
+                self.next_char();
+            } else if c == term.term {
+                if nest == 0 {
+                    break;
+                }
+                nest -= 1;
+            } else if Some(c) == term.paren {
+                nest += 1;
+            }
+        }
+        self.lex_strterm.pop();
+    }
+
+    /// Skips to the end of the line, or to the closing delimiter of the enclosing brackets or
+    /// interpolation
+    fn skip_line(&mut self) {
+        let interpolating = self
+            .lex_strterm
+            .last()
+            .is_some_and(|t| t.embedded.is_some());
+        while let Some(c) = self.char(0) {
+            match c {
+                '\n' => break,
+                ')' | ']' if self.nesting_level > 0 => break,
+                '}' if self.nesting_level > 0 || interpolating => break,
+                _ => self.next_char(),
+            };
+        }
+    }
+}
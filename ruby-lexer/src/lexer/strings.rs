@@ -0,0 +1,782 @@
+use super::heredocs::{Heredoc, LineIndent};
+use super::{
+    LexResult, LexState, Lexer, LexicalError, Location, SourceEncoding, StringValue, Token,
+    WarningKind,
+};
+use std::borrow::Cow;
+
+bitflags! {
+    /// Describes the type of string literal being lexed (mruby's `mrb_string_type`)
+    #[derive(Default)]
+    pub struct StringType: u32 {
+        const STR_PARSING       = 0x01;
+        const STR_EXPAND        = 0x02; /* interpolation and escapes are processed */
+        const STR_REGEXP        = 0x04;
+        const STR_WORD          = 0x08;
+        const STR_SYMBOL        = 0x10;
+        const STR_ARRAY         = 0x20;
+        const STR_HEREDOC       = 0x40;
+        const STR_XQUOTE        = 0x80;
+        const STR_LABEL         = 0x100; /* a quoted label (`"key":`) is allowed */
+        const NOT_PARSING       = 0;
+        const SQUOTE            = Self::STR_PARSING.bits();
+        const DQUOTE            = (Self::STR_PARSING.bits() | Self::STR_EXPAND.bits());
+        const HEREDOC           = (Self::STR_PARSING.bits() | Self::STR_HEREDOC.bits());
+        const SWORD             = (Self::STR_PARSING.bits() | Self::STR_WORD.bits() | Self::STR_ARRAY.bits());
+        const DWORD             = (Self::SWORD.bits() | Self::STR_EXPAND.bits());
+        const SSYM              = (Self::STR_PARSING.bits() | Self::STR_SYMBOL.bits());
+        const DSYM              = (Self::SSYM.bits() | Self::STR_EXPAND.bits());
+        const SSYMBOLS          = (Self::SSYM.bits() | Self::STR_ARRAY.bits());
+        const DSYMBOLS          = (Self::SSYMBOLS.bits() | Self::STR_EXPAND.bits());
+        const REGEXP            = (Self::STR_PARSING.bits() | Self::STR_REGEXP.bits() | Self::STR_EXPAND.bits());
+        const XQUOTE            = (Self::STR_PARSING.bits() | Self::STR_XQUOTE.bits() | Self::STR_EXPAND.bits());
+    }
+}
+
+/// Tracks a string literal whose contents are being lexed (mruby's `lex_strterm`)
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct StringTerm {
+    /// The type of string literal
+    pub kind: StringType,
+    /// The character that terminates the literal
+    pub term: char,
+    /// The opening delimiter of the literal, if it may be nested within the literal
+    pub paren: Option<char>,
+    /// The number of nested delimiter pairs currently open within the literal
+    pub nest: usize,
+    /// The location of the literal's opening delimiter
+    pub start: Location,
+    /// Whether a segment of the literal has already been emitted
+    pub interpolated: bool,
+    /// The interpolated code currently being lexed, if any
+    pub embedded: Option<Embedded>,
+    /// Describes the heredoc being lexed, if the literal is a heredoc
+    pub heredoc: Option<Heredoc>,
+}
+
+/// Describes the code embedded within a string literal
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Embedded {
+    /// A `#@ivar`, `#@@cvar` or `#$gvar` shorthand
+    Variable,
+    /// A `#{...}` expression, opened at the given nesting level
+    Expression { nesting_level: usize },
+}
+
+impl<'src> Lexer<'src> {
+    /// Lexes the start of a quoted string literal along with its first segment
+    pub(crate) fn lex_string(&mut self, kind: StringType) -> LexResult<'src> {
+        let start = self.get_pos();
+        let term = self.next_char().unwrap();
+        self.begin_string(kind, term, None, start.clone());
+        self.lex_string_content(start)
+    }
+
+    /// Lexes the start of a double or single-quoted string literal, which is a label when it
+    /// is followed by a `:` where a label is possible (e.g. `{"key": value}`)
+    pub(crate) fn lex_quoted_string(&mut self, mut kind: StringType) -> LexResult<'src> {
+        if self.is_label_possible() {
+            kind |= StringType::STR_LABEL;
+        }
+        self.lex_string(kind)
+    }
+
+    /// Pushes a new string literal onto the lexer's string terminator stack
+    pub(crate) fn begin_string(
+        &mut self,
+        kind: StringType,
+        term: char,
+        paren: Option<char>,
+        start: Location,
+    ) {
+        self.lex_strterm.push(StringTerm {
+            kind,
+            term,
+            paren,
+            nest: 0,
+            start,
+            interpolated: false,
+            embedded: None,
+            heredoc: None,
+        });
+    }
+
+    /// Lexes a percent literal (`%q`, `%Q`, `%w`, `%W`, `%i`, `%I`, `%s`, `%x` or `%r`)
+    ///
+    /// A `%` followed by a non-alphanumeric delimiter is treated as `%Q`. Paired delimiters
+    /// (`()`, `[]`, `{}` and `<>`) may be nested within the literal.
+    pub(crate) fn lex_percent_literal(&mut self) -> LexResult<'src> {
+        let start = self.get_pos();
+        self.next_char(); // Discard the '%'
+
+        let c = match self.next_char() {
+            Some(c) => c,
+            None => return Err(Self::unterminated_quoted_string(start)),
+        };
+        let (c, term) = if c.is_ascii_alphanumeric() {
+            match self.next_char() {
+                Some(term) if term.is_ascii_alphanumeric() => {
+                    return Err(Self::unknown_percent_literal(start))
+                }
+                Some(term) => (c, term),
+                None => return Err(Self::unterminated_quoted_string(start)),
+            }
+        } else {
+            ('Q', c)
+        };
+        let (term, paren) = match term {
+            '(' => (')', Some('(')),
+            '[' => (']', Some('[')),
+            '{' => ('}', Some('{')),
+            '<' => ('>', Some('<')),
+            term => (term, None),
+        };
+        let kind = match c {
+            'Q' => StringType::DQUOTE,
+            'q' => StringType::SQUOTE,
+            'W' => StringType::DWORD,
+            'w' => StringType::SWORD,
+            'I' => StringType::DSYMBOLS,
+            'i' => StringType::SSYMBOLS,
+            's' => StringType::SSYM,
+            'x' => StringType::XQUOTE,
+            'r' => StringType::REGEXP,
+            _ => return Err(Self::unknown_percent_literal(start)),
+        };
+        self.begin_string(kind, term, paren, start.clone());
+
+        // Lists emit a token for their opening delimiter, followed by one literal per element
+        if kind.contains(StringType::STR_ARRAY) {
+            self.lex_state = LexState::EXPR_BEG;
+            let token = if kind.contains(StringType::STR_SYMBOL) {
+                Token::SymbolsStart
+            } else {
+                Token::WordsStart
+            };
+            return Ok((start, token, self.get_pos()));
+        }
+        self.lex_string_content(start)
+    }
+
+    /// Lexes a character literal (e.g. `?a`, `?\n` or `?\C-\M-x`), or the ternary operator's `?`
+    pub(crate) fn lex_character_literal(&mut self) -> LexResult<'src> {
+        let start = self.get_pos();
+        if self.is_end() {
+            self.lex_state = LexState::EXPR_VALUE;
+            return self.emit_from_chars(Token::OpTernaryIf, 1);
+        }
+        let c = match self.char(1) {
+            Some(c) => c,
+            None => {
+                return Err(LexicalError {
+                    message: "incomplete character syntax".to_owned(),
+                    location: start,
+                })
+            }
+        };
+        if Self::is_space(c) {
+            if !self.is_arg() {
+                let escape = match c {
+                    ' ' => Some('s'),
+                    '\n' => Some('n'),
+                    '\t' => Some('t'),
+                    '\x0b' => Some('v'),
+                    '\r' => Some('r'),
+                    '\x0c' => Some('f'),
+                    _ => None,
+                };
+                if let Some(escape) = escape {
+                    self.warn(
+                        WarningKind::InvalidCharacterSyntax,
+                        &format!("invalid character syntax; use ?\\{}", escape),
+                    );
+                }
+            }
+            self.lex_state = LexState::EXPR_VALUE;
+            return self.emit_from_chars(Token::OpTernaryIf, 1);
+        }
+        // A '?' followed by a word is the ternary operator
+        if (c.is_ascii_alphanumeric() || c == '_') && self.char(2).is_some_and(Self::is_identchar) {
+            self.lex_state = LexState::EXPR_VALUE;
+            return self.emit_from_chars(Token::OpTernaryIf, 1);
+        }
+
+        self.next_char(); // Discard the '?'
+        let content_start = self.location.offset();
+        let mut buf: Vec<u8> = Vec::new();
+        if c == '\\' {
+            if self.char(1) == Some('u') {
+                self.next_char();
+                self.next_char();
+                let chars = self.read_unicode_escape()?;
+                if chars.len() != 1 {
+                    return Err(LexicalError {
+                        message: "invalid character syntax".to_owned(),
+                        location: start,
+                    });
+                }
+                self.push_char(&mut buf, chars[0]);
+            } else {
+                self.read_string_escape(&mut buf)?;
+            }
+        } else {
+            self.push_next_char(&mut buf);
+        }
+
+        self.lex_state = LexState::EXPR_ENDARG;
+        Ok((
+            start,
+            Token::Char {
+                value: self.string_from_bytes(buf, content_start),
+            },
+            self.get_pos(),
+        ))
+    }
+
+    /// Lexes the next segment of the innermost string literal
+    ///
+    /// A literal without interpolation is emitted as a single token (e.g. `Token::String`).
+    /// Otherwise, the segment that opens the literal is emitted as a `Token::StringPart`,
+    /// segments between two interpolations are emitted as `Token::StringMid` and the closing
+    /// segment is emitted as the literal's token. Each segment includes the delimiters and
+    /// interpolation markers surrounding it.
+    ///
+    /// The elements of `%w`, `%W`, `%i` and `%I` lists are each lexed as their own literal,
+    /// with the list's closing delimiter emitted as `Token::ListEnd`.
+    pub(crate) fn lex_string_content(&mut self, mut tok_start: Location) -> LexResult<'src> {
+        let term = self.lex_strterm.last().unwrap().clone();
+        let expand = term.kind.contains(StringType::STR_EXPAND);
+        let list = term.kind.contains(StringType::STR_ARRAY);
+        let regexp = term.kind.contains(StringType::STR_REGEXP);
+        let mut nest = term.nest;
+        let mut buf: Vec<u8> = Vec::new();
+        let mut line_head = term.heredoc.as_ref().is_some_and(|h| h.line_head);
+        let mut indents: Vec<LineIndent> = Vec::new();
+
+        // Skip the whitespace that separates the elements of a list
+        if list && !term.interpolated {
+            while let Some(c) = self.char(0) {
+                if !Self::is_space(c) {
+                    break;
+                }
+                self.next_char();
+            }
+            tok_start = self.get_pos();
+            if self.char(0) == Some(term.term) && nest == 0 {
+                self.next_char();
+                self.lex_strterm.pop();
+                self.lex_state = LexState::EXPR_ENDARG;
+                return Ok((tok_start, Token::ListEnd, self.get_pos()));
+            }
+        }
+
+        let content_start = self.location.offset();
+        loop {
+            // Check for the end of a heredoc at the start of each line
+            if line_head {
+                line_head = false;
+                let heredoc = term.heredoc.as_ref().unwrap();
+                if self.lex_heredoc_line_head(heredoc, &mut buf, &mut indents) {
+                    break;
+                }
+            }
+            match self.char(0) {
+                None => {
+                    self.abandon_literals();
+                    return Err(Self::unterminated_literal(&term));
+                }
+                Some(c) if list && Self::is_space(c) => break,
+                Some(c) if c == term.term && term.heredoc.is_none() => {
+                    if nest == 0 {
+                        // The closing delimiter of a list is emitted separately
+                        if !list {
+                            self.next_char();
+                        }
+                        break;
+                    }
+                    nest -= 1;
+                    self.push_next_char(&mut buf);
+                }
+                Some(c) if Some(c) == term.paren => {
+                    nest += 1;
+                    self.push_next_char(&mut buf);
+                }
+                Some('\\') => match self.char(1) {
+                    // Escaped whitespace is part of a list's element
+                    Some(c) if list && Self::is_space(c) => {
+                        self.next_char();
+                        self.push_next_char(&mut buf);
+                    }
+                    _ if regexp => self.read_regexp_escape(&term, &mut buf),
+                    _ if expand => self.read_string_escape(&mut buf)?,
+                    // Backslashes are never special in single-quoted heredocs
+                    _ if term.heredoc.is_some() => {
+                        self.push_next_char(&mut buf);
+                    }
+                    // Only delimiters and backslashes may be escaped in single quotes
+                    Some(c) if c == term.term || Some(c) == term.paren || c == '\\' => {
+                        self.next_char();
+                        self.push_next_char(&mut buf);
+                    }
+                    _ => self.push_next_char(&mut buf),
+                },
+                Some('#') if expand => {
+                    if let Some(embedded) = self.peek_interpolation() {
+                        // Consume the '#{' or '#'
+                        self.next_char();
+                        if let Embedded::Expression { .. } = embedded {
+                            self.next_char();
+                        }
+                        if term.heredoc.as_ref().is_some_and(|h| h.squiggly) {
+                            self.heredoc_indents = Some(indents);
+                        }
+                        let value = self.string_from_bytes(buf, content_start);
+                        let token = if term.interpolated {
+                            Token::StringMid { value }
+                        } else {
+                            Token::StringPart { value }
+                        };
+                        let strterm = self.lex_strterm.last_mut().unwrap();
+                        strterm.nest = nest;
+                        strterm.interpolated = true;
+                        strterm.embedded = Some(embedded);
+                        if let Some(heredoc) = strterm.heredoc.as_mut() {
+                            heredoc.line_head = false;
+                        }
+                        if let Embedded::Expression { .. } = embedded {
+                            self.push_nesting();
+                        }
+                        self.lex_state = LexState::EXPR_BEG;
+                        self.command_state = true;
+                        return Ok((tok_start, token, self.get_pos()));
+                    }
+                    self.push_next_char(&mut buf);
+                }
+                Some('\n') => {
+                    self.push_next_char(&mut buf);
+                    line_head = term.heredoc.is_some();
+                }
+                Some(_) => self.push_next_char(&mut buf),
+            }
+        }
+
+        if list {
+            // Only the current element has ended
+            let strterm = self.lex_strterm.last_mut().unwrap();
+            strterm.nest = nest;
+            strterm.interpolated = false;
+        } else {
+            self.lex_strterm.pop();
+            self.lex_state = LexState::EXPR_ENDARG;
+        }
+        if let Some(heredoc) = term.heredoc {
+            // Resume lexing the line that follows the heredoc's body
+            self.lex_state = heredoc.resume_state;
+            self.command_state = heredoc.resume_command_state;
+            if heredoc.squiggly {
+                self.heredoc_indents = Some(indents);
+            }
+        }
+        let value = self.string_from_bytes(buf, content_start);
+        let token = if regexp {
+            let options = self.read_regexp_options()?;
+            Token::Regex { value, options }
+        } else if term.kind.contains(StringType::STR_LABEL) && self.is_label_suffix(0) {
+            self.next_char();
+            self.lex_state = LexState::EXPR_ARG | LexState::EXPR_LABELED;
+            Token::LabelTag {
+                value: value.into_string_lossy(),
+            }
+        } else if term.kind.contains(StringType::STR_XQUOTE) {
+            Token::XString { value }
+        } else if term.kind.contains(StringType::STR_SYMBOL) {
+            Token::Symbol {
+                value: value.into_string_lossy(),
+            }
+        } else {
+            Token::String { value }
+        };
+        Ok((tok_start, token, self.get_pos()))
+    }
+
+    /// Determines whether the upcoming '#' begins an interpolation
+    fn peek_interpolation(&self) -> Option<Embedded> {
+        match self.char(1) {
+            Some('{') => Some(Embedded::Expression {
+                nesting_level: self.nesting_level,
+            }),
+            Some('@') => {
+                let i = if self.char(2) == Some('@') { 3 } else { 2 };
+                match self.char(i) {
+                    Some(c) if Self::is_identchar(c) && !c.is_ascii_digit() => {
+                        Some(Embedded::Variable)
+                    }
+                    _ => None,
+                }
+            }
+            Some('$') => match self.char(2) {
+                Some('-') if self.char(3).is_some() => Some(Embedded::Variable),
+                Some(c) if Self::is_identchar(c) || Self::is_global_name_punct(c) => {
+                    Some(Embedded::Variable)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Resumes lexing the innermost string literal if the upcoming `}` closes its interpolation
+    pub(crate) fn close_interpolation(&mut self) -> Option<LexResult<'src>> {
+        let strterm = self.lex_strterm.last_mut()?;
+        match strterm.embedded {
+            Some(Embedded::Expression { nesting_level }) if nesting_level == self.nesting_level => {
+                strterm.embedded = None;
+                let tok_start = self.get_pos();
+                self.next_char(); // Discard the '}'
+                self.pop_nesting();
+                Some(self.lex_string_content(tok_start))
+            }
+            _ => None,
+        }
+    }
+
+    /// Reads an escape sequence within an expanding string literal, starting at its backslash
+    ///
+    /// Line continuations are dropped, `\u` escapes are encoded in the source encoding and all
+    /// other escape sequences are appended to the buffer as the byte they represent.
+    pub(crate) fn read_string_escape(&mut self, buf: &mut Vec<u8>) -> Result<(), LexicalError> {
+        match self.char(1) {
+            Some('\n') => {
+                self.next_char();
+                self.next_char();
+            }
+            Some('u') => {
+                self.next_char();
+                self.next_char();
+                for c in self.read_unicode_escape()? {
+                    self.push_char(buf, c);
+                }
+            }
+            Some(c) if !c.is_ascii() => {
+                // Escaped multi-byte characters represent themselves
+                self.next_char();
+                self.push_next_char(buf);
+            }
+            _ => {
+                self.next_char();
+                let c = self.read_escape()?;
+                buf.push(c);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads an escape sequence within a regular expression, starting at its backslash
+    ///
+    /// Escape sequences are left intact for the regular expression engine to interpret, with
+    /// the exception of line continuations and escaped delimiters that are not metacharacters.
+    pub(crate) fn read_regexp_escape(&mut self, term: &StringTerm, buf: &mut Vec<u8>) {
+        self.next_char(); // Discard the '\\'
+        match self.char(0) {
+            Some('\n') => {
+                self.next_char();
+            }
+            Some(c) if c == term.term && !Self::is_simple_regexp_meta(c) => {
+                self.push_next_char(buf);
+            }
+            Some(_) => {
+                buf.push(b'\\');
+                self.push_next_char(buf);
+            }
+            None => buf.push(b'\\'),
+        }
+    }
+
+    /// Reads the option flags that follow the closing delimiter of a regular expression
+    pub(crate) fn read_regexp_options(&mut self) -> Result<Cow<'src, str>, LexicalError> {
+        let pos = self.get_pos();
+        let mut unknown = String::new();
+        while let Some(c) = self.char(0) {
+            if !c.is_ascii_alphabetic() {
+                break;
+            }
+            match self.next_char().unwrap() {
+                'i' | 'm' | 'x' | 'o' | 'u' | 'e' | 's' | 'n' => {}
+                c => unknown.push(c),
+            }
+        }
+        if !unknown.is_empty() {
+            return Err(LexicalError {
+                message: format!(
+                    "unknown regexp option{} - {}",
+                    if unknown.len() > 1 { "s" } else { "" },
+                    unknown
+                ),
+                location: pos,
+            });
+        }
+        Ok(self.slice_from(&pos))
+    }
+
+    /// Checks if the given character has a special meaning when unescaped in a regular expression
+    fn is_simple_regexp_meta(c: char) -> bool {
+        matches!(
+            c,
+            '$' | '*' | '+' | '.' | '?' | '^' | '|' | ')' | ']' | '}' | '>'
+        )
+    }
+
+    /// Reads the escape sequence following a backslash, returning the byte it represents
+    pub(crate) fn read_escape(&mut self) -> Result<u8, LexicalError> {
+        let pos = self.get_pos();
+        let c = match self.next_char() {
+            Some(c) => c,
+            None => return Err(Self::invalid_escape(pos)),
+        };
+        let byte = match c {
+            '\\' => b'\\',
+            'n' => b'\n',
+            't' => b'\t',
+            'r' => b'\r',
+            'f' => 0x0c,
+            'v' => 0x0b,
+            'a' => 0x07,
+            'e' => 0x1b,
+            'b' => 0x08,
+            's' => b' ',
+            '0'..='7' => {
+                // Octal escapes consume up to 3 digits
+                let mut value = c.to_digit(8).unwrap();
+                for _ in 0..2 {
+                    match self.char(0).and_then(|c| c.to_digit(8)) {
+                        Some(digit) => {
+                            self.next_char();
+                            value = value * 8 + digit;
+                        }
+                        None => break,
+                    }
+                }
+                value as u8
+            }
+            'x' => {
+                // Hex escapes consume up to 2 digits
+                let mut value = match self.char(0).and_then(|c| c.to_digit(16)) {
+                    Some(digit) => digit,
+                    None => {
+                        return Err(LexicalError {
+                            message: "invalid hex escape".to_owned(),
+                            location: self.get_pos(),
+                        })
+                    }
+                };
+                self.next_char();
+                if let Some(digit) = self.char(0).and_then(|c| c.to_digit(16)) {
+                    self.next_char();
+                    value = value * 16 + digit;
+                }
+                value as u8
+            }
+            'M' => {
+                // Meta characters set the high bit
+                if self.next_char() != Some('-') {
+                    return Err(Self::invalid_escape(pos));
+                }
+                match self.next_char() {
+                    Some('\\') => self.read_nested_escape(pos)? | 0x80,
+                    Some(c) if c.is_ascii() => c as u8 | 0x80,
+                    _ => return Err(Self::invalid_escape(pos)),
+                }
+            }
+            'C' | 'c' => {
+                // Control characters clear bits 6 and 7
+                if c == 'C' && self.next_char() != Some('-') {
+                    return Err(Self::invalid_escape(pos));
+                }
+                match self.next_char() {
+                    Some('\\') => self.read_nested_escape(pos)? & 0x9f,
+                    Some('?') => 0x7f,
+                    Some(c) if c.is_ascii() => c as u8 & 0x9f,
+                    _ => return Err(Self::invalid_escape(pos)),
+                }
+            }
+            c if c.is_ascii() => c as u8,
+            _ => return Err(Self::invalid_escape(pos)),
+        };
+        Ok(byte)
+    }
+
+    /// Reads an escape sequence nested within a `\M-` or `\C-` escape
+    fn read_nested_escape(&mut self, pos: Location) -> Result<u8, LexicalError> {
+        if self.char(0) == Some('u') {
+            return Err(Self::invalid_escape(pos));
+        }
+        self.read_escape()
+    }
+
+    /// Reads the characters of a `\uXXXX` or `\u{X Y Z}` escape, following the `\u`
+    pub(crate) fn read_unicode_escape(&mut self) -> Result<Vec<char>, LexicalError> {
+        let mut chars = Vec::new();
+        if self.char(0) == Some('{') {
+            // Brace-delimited form containing one or more codepoints
+            self.next_char();
+            loop {
+                while self.char(0) == Some(' ') || self.char(0) == Some('\t') {
+                    self.next_char();
+                }
+                match self.char(0) {
+                    Some('}') => {
+                        self.next_char();
+                        break;
+                    }
+                    None | Some('\n') => {
+                        return Err(LexicalError {
+                            message: "unterminated Unicode escape".to_owned(),
+                            location: self.get_pos(),
+                        })
+                    }
+                    _ => {}
+                }
+                chars.push(self.read_codepoint(1, 6)?);
+                match self.char(0) {
+                    Some(' ') | Some('\t') | Some('}') | Some('\n') | None => {}
+                    _ => return Err(Self::invalid_unicode_escape(self.get_pos())),
+                }
+            }
+        } else {
+            chars.push(self.read_codepoint(4, 4)?);
+        }
+        Ok(chars)
+    }
+
+    /// Reads a single hexadecimal Unicode codepoint
+    fn read_codepoint(&mut self, min: usize, max: usize) -> Result<char, LexicalError> {
+        let pos = self.get_pos();
+        let mut value: u32 = 0;
+        let mut digits = 0;
+        while digits < max {
+            match self.char(0).and_then(|c| c.to_digit(16)) {
+                Some(digit) => {
+                    self.next_char();
+                    value = value * 16 + digit;
+                    digits += 1;
+                }
+                None => break,
+            }
+        }
+        if digits < min {
+            return Err(Self::invalid_unicode_escape(pos));
+        }
+        if value > 0x10ffff {
+            return Err(LexicalError {
+                message: "invalid Unicode codepoint (too large)".to_owned(),
+                location: pos,
+            });
+        }
+        std::char::from_u32(value).ok_or_else(|| LexicalError {
+            message: "invalid Unicode codepoint".to_owned(),
+            location: pos,
+        })
+    }
+
+    /// Appends the bytes of a character produced by an escape sequence, in the source encoding,
+    /// to a byte buffer
+    pub(crate) fn push_char(&self, buf: &mut Vec<u8>, c: char) {
+        match self.encoding {
+            SourceEncoding::Utf8 => {
+                let mut encoded = [0; 4];
+                buf.extend_from_slice(c.encode_utf8(&mut encoded).as_bytes());
+            }
+            encoding => buf.extend(encoding.encode(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+
+    /// Consumes the next character, appending its bytes in the source encoding to the buffer
+    fn push_next_char(&mut self, buf: &mut Vec<u8>) {
+        let start = self.location.offset();
+        match self.next_char().unwrap() {
+            c if c.is_ascii() => buf.push(c as u8),
+            _ => self.push_source(buf, start, self.location.offset()),
+        }
+    }
+
+    /// Appends the bytes of the source between the given offsets to the buffer, as they are in
+    /// the source encoding
+    pub(crate) fn push_source(&self, buf: &mut Vec<u8>, start: usize, end: usize) {
+        match &self.encoded_source {
+            Some(source) => buf.extend_from_slice(source.slice(start, end)),
+            None => buf.extend_from_slice(self.source[start..end].as_bytes()),
+        }
+    }
+
+    /// Converts the bytes of a lexed string, which are in the source encoding, into its token
+    /// value
+    ///
+    /// The value is borrowed from the source when the bytes are the source text starting at the
+    /// given offset (i.e. the string holds no escapes or line continuations). The bytes are kept
+    /// as they are, since byte escapes may produce sequences that are not valid in the source
+    /// encoding.
+    pub(crate) fn string_from_bytes(&self, buf: Vec<u8>, start: usize) -> StringValue<'src> {
+        match self.encoding {
+            SourceEncoding::Utf8 if self.source.as_bytes()[start..].starts_with(&buf) => {
+                self.slice(start, start + buf.len()).into()
+            }
+            encoding => StringValue::new(buf, encoding),
+        }
+    }
+
+    /// Abandons the literals left open at the end of the input, so that the input ends after the
+    /// error reporting them
+    pub(crate) fn abandon_literals(&mut self) {
+        self.lex_strterm.clear();
+        self.pending_heredocs.clear();
+        self.parsing_heredoc = false;
+    }
+
+    pub(crate) fn unterminated_literal(term: &StringTerm) -> LexicalError {
+        let message = match &term.heredoc {
+            Some(heredoc) => format!(
+                "can't find string \"{}\" anywhere before EOF",
+                heredoc.identifier
+            ),
+            None if term.kind.contains(StringType::STR_REGEXP) => {
+                "unterminated regexp meets end of file".to_owned()
+            }
+            None if term.kind.contains(StringType::STR_ARRAY) => {
+                "unterminated list meets end of file".to_owned()
+            }
+            None => "unterminated string meets end of file".to_owned(),
+        };
+        LexicalError {
+            message,
+            location: term.start.clone(),
+        }
+    }
+
+    fn unterminated_quoted_string(location: Location) -> LexicalError {
+        LexicalError {
+            message: "unterminated quoted string meets end of file".to_owned(),
+            location,
+        }
+    }
+
+    fn unknown_percent_literal(location: Location) -> LexicalError {
+        LexicalError {
+            message: "unknown type of %string".to_owned(),
+            location,
+        }
+    }
+
+    fn invalid_escape(location: Location) -> LexicalError {
+        LexicalError {
+            message: "Invalid escape character syntax".to_owned(),
+            location,
+        }
+    }
+
+    fn invalid_unicode_escape(location: Location) -> LexicalError {
+        LexicalError {
+            message: "invalid Unicode escape".to_owned(),
+            location,
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use super::strings::StringType;
+use super::{LexResult, LexState, Lexer, LexicalError, Token};
+
+impl<'src> Lexer<'src> {
+    /// Lexes a token starting with a colon: a symbol literal, `::` or the ternary operator's `:`
+    pub(crate) fn lex_colon(&mut self) -> LexResult<'src> {
+        // ::
+        if self.char(1) == Some(':') {
+            if self.is_beg() || (self.is_arg() && self.seen_whitespace) {
+                self.lex_state = LexState::EXPR_BEG;
+                return self.emit_from_chars(Token::DoubleColonPrefix, 2);
+            }
+            self.lex_state = self.state_after_dot(self.state());
+            return self.emit_from_chars(Token::DoubleColon, 2);
+        }
+        if self.is_end() || !self.char(1).is_some_and(Self::is_symbol_start) {
+            self.lex_state = LexState::EXPR_BEG;
+            return self.emit_from_chars(Token::OpTernaryElse, 1);
+        }
+        self.lex_symbol()
+    }
+
+    /// Checks if the given character may follow the `:` of a symbol literal
+    fn is_symbol_start(c: char) -> bool {
+        match c {
+            '"' | '\'' | '@' | '$' | '+' | '-' | '*' | '/' | '%' | '<' | '>' | '=' | '!' | '~'
+            | '^' | '&' | '|' | '[' | '`' => true,
+            c => Self::is_identchar(c),
+        }
+    }
+
+    /// Lexes a symbol literal (`:name`, `:"name"`, `:'name'` or an operator method such as `:+`)
+    ///
+    /// Named and operator symbols are lexed as a method name in the `EXPR_FNAME` state, after
+    /// which the resulting token is converted into a symbol.
+    fn lex_symbol(&mut self) -> LexResult<'src> {
+        let tok_start = self.get_pos();
+        self.next_char(); // Discard the ':'
+
+        // Quoted symbols
+        let kind = match self.char(0) {
+            Some('"') => Some(StringType::DSYM),
+            Some('\'') => Some(StringType::SSYM),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            let term = self.next_char().unwrap();
+            self.begin_string(kind, term, None, tok_start.clone());
+            return self.lex_string_content(tok_start);
+        }
+
+        self.lex_state = LexState::EXPR_FNAME;
+        self.seen_whitespace = false;
+        let (_, token, tok_end) = self.produce_token()?;
+        let value = match token {
+            Token::Identifier { value }
+            | Token::Constant { value }
+            | Token::FunctionIdentifier { value }
+            | Token::AssignmentLikeMethodIdentifier { value }
+            | Token::GlobalVariable { value }
+            | Token::InstanceVariable { value }
+            | Token::ClassVariable { value } => value,
+            token => match Self::operator_method_name(&token) {
+                Some(name) => name.into(),
+                None => {
+                    return Err(LexicalError {
+                        message: "invalid symbol".to_owned(),
+                        location: tok_start,
+                    })
+                }
+            },
+        };
+        self.lex_state = LexState::EXPR_END;
+        Ok((tok_start, Token::Symbol { value }, tok_end))
+    }
+
+    /// Returns the name of the method defined by an operator token, if it is a method
+    fn operator_method_name(token: &Token) -> Option<&'static str> {
+        let name = match token {
+            Token::OpPlus => "+",
+            Token::OpMinus => "-",
+            Token::OpMultiply => "*",
+            Token::OpDivide => "/",
+            Token::OpModulus => "%",
+            Token::OpExponent => "**",
+            Token::OpUnaryPlus => "+@",
+            Token::OpUnaryMinus => "-@",
+            Token::OpNot => "!",
+            Token::OpNotEqual => "!=",
+            Token::OpNotMatch => "!~",
+            Token::OpBinComplement => "~",
+            Token::OpBinXor => "^",
+            Token::OpBinAnd => "&",
+            Token::OpBinOr => "|",
+            Token::OpCompare => "<=>",
+            Token::OpDoubleEqual => "==",
+            Token::OpTripleEqual => "===",
+            Token::OpMatch => "=~",
+            Token::OpGt => ">",
+            Token::OpGtEqual => ">=",
+            Token::OpLt => "<",
+            Token::OpLtEqual => "<=",
+            Token::OpLeftShift => "<<",
+            Token::OpRightShift => ">>",
+            Token::OpElementGet => "[]",
+            Token::OpElementSet => "[]=",
+            Token::Backtick => "`",
+            _ => return None,
+        };
+        Some(name)
+    }
+}
@@ -2,8 +2,28 @@ use super::*;
 use log::trace;
 
 // Include the various test suites
+mod borrowed_values;
+mod brackets;
+mod characters;
 mod comment;
+mod encodings;
+mod globals;
+mod heredocs;
+mod incremental;
+mod keywords;
+mod labels;
+mod locations;
+mod lossless;
+mod magic_comments;
 mod numbers;
+mod operators;
+mod percent_literals;
+mod recovery;
+mod regex;
+mod strings;
+mod symbols;
+mod versions;
+mod warnings;
 mod whitespace;
 
 pub fn enable_logging() {
@@ -11,7 +31,7 @@ pub fn enable_logging() {
 }
 
 /// Lexes the source string, returning a vector of tokens or the lexical error encountered
-pub fn lex_source(source: &str) -> Result<Vec<Token>, LexicalError> {
+pub fn lex_source(source: &str) -> Result<Vec<Token<'_>>, LexicalError> {
     trace!("Lexing:\n--------\n{}\n--------", source);
     let mut lexer = make_tokenizer(source);
     let mut tokens: Vec<Token> = Vec::new();
@@ -27,3 +47,23 @@ pub fn lex_source(source: &str) -> Result<Vec<Token>, LexicalError> {
     // Return the lexed tokens
     Ok(tokens)
 }
+
+/// Lexes the source string, returning its tokens other than whitespace
+///
+/// # Panics
+///  * Panics if a lexical error is encountered.
+pub fn lex_tokens(source: &str) -> Vec<Token<'_>> {
+    lex_source(source)
+        .unwrap()
+        .into_iter()
+        .filter(|token| *token != Token::Whitespace)
+        .collect()
+}
+
+/// Returns the location of the given line and column within the source
+///
+/// # Panics
+///  * Panics if the source doesn't contain the position.
+pub fn location(source: &str, line: usize, col: usize) -> Location {
+    Location::from_source(source, line, col).unwrap()
+}
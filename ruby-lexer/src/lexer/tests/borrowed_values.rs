@@ -0,0 +1,74 @@
+use super::*;
+use std::borrow::Cow;
+
+/// Returns the values of the tokens lexed from the source, along with whether each is borrowed
+fn lex_values(source: &str) -> Vec<(String, bool)> {
+    make_tokenizer(source)
+        .filter_map(|result| match result.unwrap().1 {
+            Token::Identifier { value }
+            | Token::Constant { value }
+            | Token::InstanceVariable { value }
+            | Token::GlobalVariable { value }
+            | Token::Comment { value }
+            | Token::Symbol { value }
+            | Token::HeredocStart { value } => {
+                let borrowed = match value {
+                    Cow::Borrowed(_) => true,
+                    Cow::Owned(_) => false,
+                };
+                Some((value.into_owned(), borrowed))
+            }
+            Token::String { value } | Token::StringPart { value } => {
+                Some((value.to_string_lossy().into_owned(), value.is_borrowed()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn borrowed_values() {
+    enable_logging();
+    assert_eq!(
+        lex_values("foo Bar @baz $qux # comment"),
+        vec![
+            ("foo".to_owned(), true),
+            ("Bar".to_owned(), true),
+            ("@baz".to_owned(), true),
+            ("$qux".to_owned(), true),
+            (" comment".to_owned(), true),
+        ]
+    );
+    assert_eq!(
+        lex_values(r#"'plain' "a\tb" "c#{d}" :sym"#),
+        vec![
+            ("plain".to_owned(), true),
+            ("a\tb".to_owned(), false),
+            ("c".to_owned(), true),
+            ("d".to_owned(), true),
+            ("".to_owned(), true),
+            ("sym".to_owned(), true),
+        ]
+    );
+    // Line endings within literals are normalized
+    assert_eq!(lex_values("'a\r\nb'"), vec![("a\nb".to_owned(), false)]);
+}
+
+#[test]
+fn owned_source() {
+    enable_logging();
+    let lexer = Lexer::from_bytes(b"foo 'bar'").unwrap();
+    let values: Vec<Token<'static>> = lexer.map(|result| result.unwrap().1).collect();
+    assert_eq!(
+        values,
+        vec![
+            Token::Identifier {
+                value: "foo".into()
+            },
+            Token::Whitespace,
+            Token::String {
+                value: "bar".into()
+            },
+        ]
+    );
+}
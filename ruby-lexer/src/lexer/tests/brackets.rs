@@ -0,0 +1,166 @@
+use super::*;
+
+fn ident(value: &str) -> Token<'_> {
+    Token::Identifier {
+        value: value.into(),
+    }
+}
+
+fn int(value: isize) -> Token<'static> {
+    Token::Integer { value }
+}
+
+#[test]
+fn parentheses() {
+    enable_logging();
+    assert_eq!(
+        lex_tokens("(1)"),
+        vec![Token::LeftParen, int(1), Token::RightParen]
+    );
+    assert_eq!(
+        lex_tokens("foo(1)"),
+        vec![ident("foo"), Token::LeftParen, int(1), Token::RightParen]
+    );
+    assert_eq!(
+        lex_tokens("foo (1)"),
+        vec![ident("foo"), Token::LeftParenArg, int(1), Token::RightParen]
+    );
+    assert_eq!(
+        lex_tokens("foo(&blk)"),
+        vec![
+            ident("foo"),
+            Token::LeftParen,
+            Token::Ampersand,
+            ident("blk"),
+            Token::RightParen
+        ]
+    );
+}
+
+#[test]
+fn brackets() {
+    enable_logging();
+    assert_eq!(
+        lex_tokens("[1]"),
+        vec![Token::LeftBracket, int(1), Token::RightBracket]
+    );
+    assert_eq!(
+        lex_tokens("foo[1]"),
+        vec![
+            ident("foo"),
+            Token::LeftBracket,
+            int(1),
+            Token::RightBracket
+        ]
+    );
+    assert_eq!(
+        lex_tokens("foo [1]"),
+        vec![
+            ident("foo"),
+            Token::LeftBracket,
+            int(1),
+            Token::RightBracket
+        ]
+    );
+    assert_eq!(
+        lex_tokens("def []=(a)"),
+        vec![
+            Token::KwDef,
+            Token::OpElementSet,
+            Token::LeftParen,
+            ident("a"),
+            Token::RightParen
+        ]
+    );
+    assert_eq!(
+        lex_tokens("def [](a)"),
+        vec![
+            Token::KwDef,
+            Token::OpElementGet,
+            Token::LeftParen,
+            ident("a"),
+            Token::RightParen
+        ]
+    );
+}
+
+#[test]
+fn braces() {
+    enable_logging();
+    assert_eq!(lex_tokens("{}"), vec![Token::LeftBrace, Token::RightBrace]);
+    assert_eq!(
+        lex_tokens("foo {}"),
+        vec![ident("foo"), Token::LeftBraceBlock, Token::RightBrace]
+    );
+    assert_eq!(
+        lex_tokens("foo() {}"),
+        vec![
+            ident("foo"),
+            Token::LeftParen,
+            Token::RightParen,
+            Token::LeftBraceBlock,
+            Token::RightBrace
+        ]
+    );
+    assert_eq!(
+        lex_tokens("foo 1 {}"),
+        vec![ident("foo"), int(1), Token::LeftBraceArg, Token::RightBrace]
+    );
+    assert_eq!(
+        lex_tokens("foo = {}"),
+        vec![
+            ident("foo"),
+            Token::OpAssign,
+            Token::LeftBrace,
+            Token::RightBrace
+        ]
+    );
+    assert_eq!(
+        lex_tokens("->(a) { a }"),
+        vec![
+            Token::Lambda,
+            Token::LeftParen,
+            ident("a"),
+            Token::RightParen,
+            Token::LambdaBegin,
+            ident("a"),
+            Token::RightBrace
+        ]
+    );
+    assert_eq!(
+        lex_tokens("-> { {} }"),
+        vec![
+            Token::Lambda,
+            Token::LambdaBegin,
+            Token::LeftBrace,
+            Token::RightBrace,
+            Token::RightBrace
+        ]
+    );
+}
+
+#[test]
+fn unbalanced_closers() {
+    enable_logging();
+    assert_eq!(
+        lex_source("1)"),
+        Err(LexicalError {
+            message: "syntax error, unexpected ')'".to_owned(),
+            location: location("1)", 1, 2)
+        })
+    );
+    assert_eq!(
+        lex_source("[]]"),
+        Err(LexicalError {
+            message: "syntax error, unexpected ']'".to_owned(),
+            location: location("[]]", 1, 3)
+        })
+    );
+    assert_eq!(
+        lex_source("}"),
+        Err(LexicalError {
+            message: "syntax error, unexpected '}'".to_owned(),
+            location: location("}", 1, 1)
+        })
+    );
+}
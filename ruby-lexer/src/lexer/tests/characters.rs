@@ -0,0 +1,104 @@
+use super::*;
+
+fn char(value: &str) -> Result<Vec<Token<'_>>, LexicalError> {
+    Ok(vec![Token::Char {
+        value: value.into(),
+    }])
+}
+
+/// Returns the token of a character literal whose bytes don't form a valid UTF-8 character
+fn char_bytes(bytes: &[u8]) -> Result<Vec<Token<'_>>, LexicalError> {
+    Ok(vec![Token::Char {
+        value: StringValue::new(bytes, SourceEncoding::Utf8),
+    }])
+}
+
+#[test]
+fn character_literals() {
+    enable_logging();
+    assert_eq!(lex_source("?a"), char("a"));
+    assert_eq!(lex_source("?Z"), char("Z"));
+    assert_eq!(lex_source("?_"), char("_"));
+    assert_eq!(lex_source("??"), char("?"));
+    assert_eq!(lex_source("?#"), char("#"));
+    assert_eq!(lex_source("?あ"), char("あ"));
+    assert_eq!(
+        lex_source("?a?1"),
+        Ok(vec![
+            Token::Char { value: "a".into() },
+            Token::OpTernaryIf,
+            Token::Integer { value: 1 }
+        ])
+    );
+}
+
+#[test]
+fn escaped_character_literals() {
+    enable_logging();
+    assert_eq!(lex_source("?\\n"), char("\n"));
+    assert_eq!(lex_source("?\\s"), char(" "));
+    assert_eq!(lex_source("?\\\\"), char("\\"));
+    assert_eq!(lex_source("?\\101"), char("A"));
+    assert_eq!(lex_source("?\\x41"), char("A"));
+    assert_eq!(lex_source("?\\u0041"), char("A"));
+    assert_eq!(lex_source("?\\u{1F600}"), char("\u{1F600}"));
+    assert_eq!(lex_source("?\\C-a"), char("\x01"));
+    assert_eq!(lex_source("?\\ca"), char("\x01"));
+    assert_eq!(lex_source("?\\c?"), char("\x7f"));
+    assert_eq!(lex_source("?\\M-a"), char_bytes(b"\xe1"));
+    assert_eq!(lex_source("?\\C-\\M-a"), char_bytes(b"\x81"));
+    assert_eq!(lex_source("?\\M-\\C-a"), char_bytes(b"\x81"));
+}
+
+#[test]
+fn ternary_operator() {
+    enable_logging();
+    assert_eq!(
+        lex_source("@a ? 1"),
+        Ok(vec![
+            Token::InstanceVariable { value: "@a".into() },
+            Token::Whitespace,
+            Token::OpTernaryIf,
+            Token::Whitespace,
+            Token::Integer { value: 1 }
+        ])
+    );
+    assert_eq!(
+        lex_source("@a?1"),
+        Ok(vec![
+            Token::InstanceVariable { value: "@a".into() },
+            Token::OpTernaryIf,
+            Token::Integer { value: 1 }
+        ])
+    );
+    assert_eq!(
+        lex_source("?ab"),
+        Ok(vec![
+            Token::OpTernaryIf,
+            Token::Identifier { value: "ab".into() }
+        ])
+    );
+    assert_eq!(
+        lex_source("? "),
+        Ok(vec![Token::OpTernaryIf, Token::Whitespace])
+    );
+}
+
+#[test]
+fn character_literal_errors() {
+    enable_logging();
+    assert_eq!(
+        lex_source("?"),
+        Err(LexicalError {
+            message: "incomplete character syntax".to_owned(),
+            location: location("?", 1, 1)
+        })
+    );
+    assert_eq!(
+        lex_source("?\\u{41 42}"),
+        Err(LexicalError {
+            message: "invalid character syntax".to_owned(),
+            location: location("?\\u{41 42}", 1, 1)
+        })
+    );
+}
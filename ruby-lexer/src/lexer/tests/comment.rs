@@ -4,19 +4,14 @@ use super::*;
 fn single_line_comments() {
     // Test comment only
     let mut tokens = lex_source("#");
-    assert_eq!(
-        tokens,
-        Ok(vec![Token::Comment {
-            value: "".to_owned()
-        }])
-    );
+    assert_eq!(tokens, Ok(vec![Token::Comment { value: "".into() }]));
 
     // Test a single line comment
     tokens = lex_source("# comment goes here");
     assert_eq!(
         tokens,
         Ok(vec![Token::Comment {
-            value: " comment goes here".to_owned()
+            value: " comment goes here".into()
         }])
     );
 
@@ -26,11 +21,11 @@ fn single_line_comments() {
         tokens,
         Ok(vec![
             Token::Comment {
-                value: " first comment".to_owned()
+                value: " first comment".into()
             },
             Token::Newline,
             Token::Comment {
-                value: "second\tcomment".to_owned()
+                value: "second\tcomment".into()
             }
         ])
     );
@@ -41,18 +36,18 @@ fn single_line_comments() {
         tokens,
         Ok(vec![
             Token::Identifier {
-                value: "foo".to_owned()
+                value: "foo".into()
             },
             Token::Comment {
-                value: " first comment".to_owned()
+                value: " first comment".into()
             },
             Token::LineTerminator,
             Token::Identifier {
-                value: "bar".to_owned()
+                value: "bar".into()
             },
             Token::Whitespace,
             Token::Comment {
-                value: " second comment".to_owned()
+                value: " second comment".into()
             },
             Token::LineTerminator
         ])
@@ -66,18 +61,57 @@ fn multi_line_comments() {
     assert_eq!(
         tokens,
         Ok(vec![
-            Token::Comment {
-                value: "foo bar\nblah\nbaz".to_owned()
+            Token::EmbeddedDocument {
+                value: "foo bar\nblah\nbaz".into()
             },
-            Token::LineTerminator,
+            Token::Newline,
+            Token::Identifier {
+                value: "meh".into()
+            }
+        ])
+    );
+
+    // Test a comment ending the file
+    let tokens = lex_source("foo\n=begin\n# bar\n=end");
+    assert_eq!(
+        tokens,
+        Ok(vec![
             Token::Identifier {
-                value: "meh".to_owned()
+                value: "foo".into()
+            },
+            Token::LineTerminator,
+            Token::EmbeddedDocument {
+                value: "# bar\n".into()
             }
         ])
     );
+
+    // Test an unterminated comment
     let tokens = lex_source("=begin stuff\nblah\n");
     assert_eq!(
         tokens,
-        Err(LexicalError { message: "".to_owned(), location: Location { line: 3, col: 1 } })
+        Err(LexicalError {
+            message: "Multi-line comment was not terminated before the end of the file".to_owned(),
+            location: location("=begin stuff\nblah\n", 3, 1)
+        })
+    );
+
+    // Test that comments are only recognized at the beginning of a line
+    let tokens = lex_source("=begin\n =end\n=endless\n=end");
+    assert_eq!(
+        tokens,
+        Ok(vec![Token::EmbeddedDocument {
+            value: " =end\n=endless\n".into()
+        }])
+    );
+    let tokens = lex_source("@a =begin");
+    assert_eq!(
+        tokens,
+        Ok(vec![
+            Token::InstanceVariable { value: "@a".into() },
+            Token::Whitespace,
+            Token::OpAssign,
+            Token::KwBegin
+        ])
     );
 }
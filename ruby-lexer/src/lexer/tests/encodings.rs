@@ -0,0 +1,183 @@
+use super::*;
+
+/// Lexes the source bytes, returning the source encoding and the tokens other than whitespace
+/// and newlines
+fn lex_bytes(source: &[u8]) -> (SourceEncoding, Vec<Token<'static>>) {
+    let mut lexer = Lexer::from_bytes(source).unwrap();
+    let tokens = lexer
+        .by_ref()
+        .map(|x| x.unwrap().1)
+        .filter(|token| {
+            !matches!(
+                token,
+                Token::Whitespace | Token::Comment { .. } | Token::Newline
+            )
+        })
+        .collect();
+    (lexer.source_encoding(), tokens)
+}
+
+fn string(value: &str) -> Token<'_> {
+    Token::String {
+        value: value.into(),
+    }
+}
+
+/// Returns the token of a string literal holding the given bytes in the given encoding
+fn encoded_string(bytes: &[u8], encoding: SourceEncoding) -> Token<'_> {
+    Token::String {
+        value: StringValue::new(bytes, encoding),
+    }
+}
+
+#[test]
+fn utf8_sources() {
+    enable_logging();
+    assert_eq!(
+        lex_bytes(b"'caf\xC3\xA9'"),
+        (SourceEncoding::Utf8, vec![string("café")])
+    );
+    // The byte order mark is skipped
+    let mut lexer = Lexer::from_bytes(b"\xEF\xBB\xBF# coding: euc-jp\n:a").unwrap();
+    assert_eq!(
+        lexer.next(),
+        Some(Ok((
+            Location::new(1, 1),
+            Token::Comment {
+                value: " coding: euc-jp".into()
+            },
+            location("# coding: euc-jp", 1, 17)
+        )))
+    );
+    assert_eq!(lexer.source_encoding(), SourceEncoding::Utf8);
+}
+
+#[test]
+fn japanese_sources() {
+    enable_logging();
+    let shift_jis = SourceEncoding::for_name("Shift_JIS").unwrap();
+    let (encoding, tokens) = lex_bytes(b"# coding: Shift_JIS\n'\x82\xA0' \"\\x82\\xA2\"");
+    assert_eq!(encoding, shift_jis);
+    assert_eq!(
+        tokens,
+        vec![
+            encoded_string(b"\x82\xA0", shift_jis),
+            encoded_string(b"\x82\xA2", shift_jis)
+        ]
+    );
+    match &tokens[0] {
+        Token::String { value } => assert_eq!(value.to_str(), Some("あ".into())),
+        token => panic!("unexpected token: {:?}", token),
+    }
+    assert_eq!(encoding.encode("あい"), b"\x82\xA0\x82\xA2".to_vec());
+    assert_eq!(SourceEncoding::for_name("Windows-31J"), Some(shift_jis));
+
+    let (encoding, tokens) = lex_bytes(b"#!/usr/bin/ruby\n# -*- coding: euc-jp -*-\n:\xA4\xA2");
+    assert_eq!(encoding, SourceEncoding::for_name("EUC-JP").unwrap());
+    assert_eq!(
+        tokens,
+        vec![Token::Symbol {
+            value: "あ".into()
+        }]
+    );
+}
+
+#[test]
+fn single_byte_sources() {
+    enable_logging();
+    let (encoding, tokens) = lex_bytes(b"# encoding: iso-8859-1\n'caf\xE9'");
+    assert_eq!(encoding, SourceEncoding::Latin1);
+    assert_eq!(tokens, vec![encoded_string(b"caf\xE9", encoding)]);
+    match &tokens[0] {
+        Token::String { value } => assert_eq!(value.to_str(), Some("café".into())),
+        token => panic!("unexpected token: {:?}", token),
+    }
+    assert_eq!(encoding.encode("café"), b"caf\xE9".to_vec());
+
+    let (encoding, tokens) = lex_bytes(b"# encoding: binary\n'\x80'");
+    assert_eq!(encoding, SourceEncoding::Binary);
+    assert_eq!(tokens, vec![encoded_string(b"\x80", encoding)]);
+}
+
+#[test]
+fn duplicate_encoded_characters() {
+    enable_logging();
+    // Windows-31J encodes some characters twice (e.g. `纊` as both an NEC-selected IBM extension
+    // and an IBM extension), so the bytes of literals are kept as they appear in the source
+    let shift_jis = SourceEncoding::for_name("Windows-31J").unwrap();
+    assert_eq!(shift_jis.encode("纊≒"), b"\xFA\x5C\x81\xE0".to_vec());
+    let (_, tokens) = lex_bytes(b"# coding: cp932\n'\xED\x40\x87\x90' \"\xED\x40\\n\"");
+    assert_eq!(
+        tokens,
+        vec![
+            encoded_string(b"\xED\x40\x87\x90", shift_jis),
+            encoded_string(b"\xED\x40\n", shift_jis),
+        ]
+    );
+    let (_, tokens) = lex_bytes(b"# coding: cp932\nx = <<~EOS\n  \x87\x90\n  EOS\n");
+    assert_eq!(
+        tokens.last(),
+        Some(&encoded_string(b"\x87\x90\n", shift_jis))
+    );
+}
+
+#[test]
+fn invalid_escaped_bytes() {
+    enable_logging();
+    // Escaped bytes are kept as they are, even when they don't form a character
+    let shift_jis = SourceEncoding::for_name("Shift_JIS").unwrap();
+    let (_, tokens) = lex_bytes(b"# coding: Shift_JIS\n\"\\x82\"");
+    assert_eq!(tokens, vec![encoded_string(b"\x82", shift_jis)]);
+    match &tokens[0] {
+        Token::String { value } => {
+            assert_eq!(value.to_str(), None);
+            assert_eq!(value.to_string_lossy(), "\u{fffd}");
+        }
+        token => panic!("unexpected token: {:?}", token),
+    }
+}
+
+#[test]
+fn encoded_squiggly_heredocs() {
+    enable_logging();
+    // Indentation is measured and removed in the bytes of the source encoding
+    let shift_jis = SourceEncoding::for_name("Shift_JIS").unwrap();
+    let (_, tokens) = lex_bytes(b"# coding: Shift_JIS\nx = <<~EOS\n  \x82\xA0\n    b\nEOS\n");
+    assert_eq!(
+        tokens.last(),
+        Some(&encoded_string(b"\x82\xA0\n  b\n", shift_jis))
+    );
+}
+
+#[test]
+fn encoding_errors() {
+    enable_logging();
+    assert_eq!(
+        Lexer::from_bytes(b"# encoding: klingon\n").err(),
+        Some(LexicalError {
+            message: "unknown encoding name: klingon".to_owned(),
+            location: Location::new(1, 1)
+        })
+    );
+    assert_eq!(
+        Lexer::from_bytes(b"# encoding: utf-16le\n").err(),
+        Some(LexicalError {
+            message: "unknown encoding name: utf-16le".to_owned(),
+            location: Location::new(1, 1)
+        })
+    );
+    assert_eq!(
+        Lexer::from_bytes(b"foo\n'\xFF'").err(),
+        Some(LexicalError {
+            message: "invalid multibyte char (UTF-8)".to_owned(),
+            location: location("foo\n'", 2, 2)
+        })
+    );
+    assert_eq!(
+        Lexer::from_bytes(b"# coding: sjis\n'\x82'").err(),
+        Some(LexicalError {
+            message: "invalid multibyte char (Shift_JIS)".to_owned(),
+            location: location("# coding: sjis\n'", 2, 2)
+        })
+    );
+}
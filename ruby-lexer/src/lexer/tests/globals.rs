@@ -0,0 +1,90 @@
+use super::*;
+
+fn global(value: &str) -> Result<Vec<Token<'_>>, LexicalError> {
+    Ok(vec![Token::GlobalVariable {
+        value: value.into(),
+    }])
+}
+
+#[test]
+fn named_global_variables() {
+    enable_logging();
+    assert_eq!(lex_source("$foo"), global("$foo"));
+    assert_eq!(lex_source("$FOO"), global("$FOO"));
+    assert_eq!(lex_source("$_"), global("$_"));
+    assert_eq!(lex_source("$_foo"), global("$_foo"));
+    assert_eq!(lex_source("$0"), global("$0"));
+    assert_eq!(lex_source("$stdout"), global("$stdout"));
+}
+
+#[test]
+fn special_global_variables() {
+    enable_logging();
+    for name in &[
+        "$!", "$@", "$~", "$;", "$,", "$/", "$\\", "$*", "$$", "$?", "$:", "$\"", "$<", "$>", "$.",
+        "$=",
+    ] {
+        assert_eq!(lex_source(name), global(name));
+    }
+    assert_eq!(lex_source("$-w"), global("$-w"));
+    assert_eq!(lex_source("$-0"), global("$-0"));
+    assert_eq!(
+        lex_source("$!.message"),
+        Ok(vec![
+            Token::GlobalVariable { value: "$!".into() },
+            Token::Dot,
+            Token::Identifier {
+                value: "message".into()
+            }
+        ])
+    );
+}
+
+#[test]
+fn regex_references() {
+    enable_logging();
+    for name in &["$&", "$`", "$'", "$+"] {
+        assert_eq!(
+            lex_source(name),
+            Ok(vec![Token::RegexBackRef {
+                value: (*name).into()
+            }])
+        );
+    }
+    assert_eq!(
+        lex_source("$1"),
+        Ok(vec![Token::RegexNthRef { value: "$1".into() }])
+    );
+    assert_eq!(
+        lex_source("$123"),
+        Ok(vec![Token::RegexNthRef {
+            value: "$123".into()
+        }])
+    );
+}
+
+#[test]
+fn invalid_global_variables() {
+    enable_logging();
+    assert_eq!(
+        lex_source("$-"),
+        Err(LexicalError {
+            message: "`$-' is not allowed as a global variable name".to_owned(),
+            location: location("$-", 1, 1)
+        })
+    );
+    assert_eq!(
+        lex_source("$%"),
+        Err(LexicalError {
+            message: "`$%' is not allowed as a global variable name".to_owned(),
+            location: location("$%", 1, 1)
+        })
+    );
+    assert_eq!(
+        lex_source("$ "),
+        Err(LexicalError {
+            message: "`$' without identifiers is not allowed as a global variable name".to_owned(),
+            location: location("$ ", 1, 1)
+        })
+    );
+}
@@ -0,0 +1,213 @@
+use super::*;
+
+fn heredoc<'a>(identifier: &'a str, body: &'a str) -> Result<Vec<Token<'a>>, LexicalError> {
+    Ok(vec![
+        Token::HeredocStart {
+            value: identifier.into(),
+        },
+        Token::LineTerminator,
+        Token::String { value: body.into() },
+    ])
+}
+
+#[test]
+fn heredocs() {
+    enable_logging();
+    assert_eq!(
+        lex_source("<<EOS\nfoo\nbar\nEOS\n"),
+        heredoc("EOS", "foo\nbar\n")
+    );
+    assert_eq!(lex_source("<<EOS\nEOS"), heredoc("EOS", ""));
+    assert_eq!(
+        lex_source("<<EOS\nEOSX\n EOS\nEOS\n"),
+        heredoc("EOS", "EOSX\n EOS\n")
+    );
+    assert_eq!(
+        lex_source("<<-EOS\n  foo\n  EOS\n"),
+        heredoc("EOS", "  foo\n")
+    );
+    assert_eq!(
+        lex_source("<<EOS\n\\tfoo\\\nbar\nEOS\n"),
+        heredoc("EOS", "\tfoobar\n")
+    );
+}
+
+#[test]
+fn quoted_heredocs() {
+    enable_logging();
+    assert_eq!(
+        lex_source("<<'EOS'\n#{foo}\\n\\\\\nEOS\n"),
+        heredoc("EOS", "#{foo}\\n\\\\\n")
+    );
+    assert_eq!(
+        lex_source("<<\"EOS\"\na\\tb\nEOS\n"),
+        heredoc("EOS", "a\tb\n")
+    );
+    assert_eq!(
+        lex_source("<<~'END OF DOC'\n  foo\n  END OF DOC\n"),
+        heredoc("END OF DOC", "foo\n")
+    );
+    assert_eq!(
+        lex_source("<<`EOS`\nls\nEOS\n"),
+        Ok(vec![
+            Token::HeredocStart {
+                value: "EOS".into()
+            },
+            Token::LineTerminator,
+            Token::XString {
+                value: "ls\n".into()
+            }
+        ])
+    );
+}
+
+#[test]
+fn heredoc_followed_by_code() {
+    enable_logging();
+    assert_eq!(
+        lex_source("<<A, <<B; @x\na\nA\nb\nB\n@y"),
+        Ok(vec![
+            Token::HeredocStart { value: "A".into() },
+            Token::Comma,
+            Token::Whitespace,
+            Token::HeredocStart { value: "B".into() },
+            Token::Semicolon,
+            Token::Whitespace,
+            Token::InstanceVariable { value: "@x".into() },
+            Token::LineTerminator,
+            Token::String {
+                value: "a\n".into()
+            },
+            Token::String {
+                value: "b\n".into()
+            },
+            Token::InstanceVariable { value: "@y".into() }
+        ])
+    );
+}
+
+#[test]
+fn squiggly_heredocs() {
+    enable_logging();
+    assert_eq!(
+        lex_source("<<~EOS\n  foo\n    bar\n\n  baz\n  EOS\n"),
+        heredoc("EOS", "foo\n  bar\n\nbaz\n")
+    );
+
+    // Whitespace-only lines are ignored when determining the indentation
+    assert_eq!(
+        lex_source("<<~EOS\n        \n    foo\n  \n  EOS\n"),
+        heredoc("EOS", "    \nfoo\n\n")
+    );
+
+    // Tabs advance to the next multiple of 8 columns
+    assert_eq!(
+        lex_source("<<~EOS\n\tone\n        two\nEOS\n"),
+        heredoc("EOS", "one\ntwo\n")
+    );
+    assert_eq!(
+        lex_source("<<~EOS\n    one\n\ttwo\nEOS\n"),
+        heredoc("EOS", "one\n\ttwo\n")
+    );
+    assert_eq!(
+        lex_source("<<~EOS\n      one\n  \t  two\nEOS\n"),
+        heredoc("EOS", "one\n\t  two\n")
+    );
+
+    // Escaped whitespace is not considered indentation
+    assert_eq!(
+        lex_source("<<~EOS\n  \\tfoo\n    bar\nEOS\n"),
+        heredoc("EOS", "\tfoo\n  bar\n")
+    );
+
+    // Indentation is removed from the bytes of the body, which may hold non-ASCII characters
+    // and escaped bytes that don't form a valid character
+    let tokens = lex_tokens("x = <<~EOS\n  \\xff\n  é\n    b\nEOS\n");
+    assert_eq!(
+        tokens.last(),
+        Some(&Token::String {
+            value: StringValue::new(&b"\xff\n\xc3\xa9\n  b\n"[..], SourceEncoding::Utf8)
+        })
+    );
+    let tokens = lex_tokens("x = <<~EOS\n  é\\xff#{@a}\n    \\xffb\nEOS\n");
+    assert_eq!(
+        tokens[tokens.len() - 3..],
+        [
+            Token::StringPart {
+                value: StringValue::new(&b"\xc3\xa9\xff"[..], SourceEncoding::Utf8)
+            },
+            Token::InstanceVariable { value: "@a".into() },
+            Token::String {
+                value: StringValue::new(&b"\n  \xffb\n"[..], SourceEncoding::Utf8)
+            }
+        ]
+    );
+}
+
+#[test]
+fn interpolated_heredocs() {
+    enable_logging();
+    assert_eq!(
+        lex_source("<<~EOS\n    a#{@b}\n    #{@c} d\n      e\n    EOS\n"),
+        Ok(vec![
+            Token::HeredocStart {
+                value: "EOS".into()
+            },
+            Token::LineTerminator,
+            Token::StringPart { value: "a".into() },
+            Token::InstanceVariable { value: "@b".into() },
+            Token::StringMid { value: "\n".into() },
+            Token::InstanceVariable { value: "@c".into() },
+            Token::String {
+                value: " d\n  e\n".into()
+            }
+        ])
+    );
+    assert_eq!(
+        lex_source("<<EOS\n#{\"EOS\"}\nEOS\n"),
+        Ok(vec![
+            Token::HeredocStart {
+                value: "EOS".into()
+            },
+            Token::LineTerminator,
+            Token::StringPart { value: "".into() },
+            Token::String {
+                value: "EOS".into()
+            },
+            Token::String { value: "\n".into() }
+        ])
+    );
+}
+
+#[test]
+fn heredoc_errors() {
+    enable_logging();
+    assert_eq!(
+        lex_source("@a = <<EOS\nfoo\n"),
+        Err(LexicalError {
+            message: "can't find string \"EOS\" anywhere before EOF".to_owned(),
+            location: location("@a = <<EOS\nfoo\n", 1, 6)
+        })
+    );
+    assert_eq!(
+        lex_source("<<~EOS"),
+        Err(LexicalError {
+            message: "can't find string \"EOS\" anywhere before EOF".to_owned(),
+            location: location("<<~EOS", 1, 1)
+        })
+    );
+    assert_eq!(
+        lex_source("<<~EOS\n  #{a\n"),
+        Err(LexicalError {
+            message: "can't find string \"EOS\" anywhere before EOF".to_owned(),
+            location: location("<<~EOS\n  #{a\n", 1, 1)
+        })
+    );
+    assert_eq!(
+        lex_source("<<\"EOS\nEOS\n"),
+        Err(LexicalError {
+            message: "unterminated here document identifier".to_owned(),
+            location: location("<<\"EOS\nEOS\n", 1, 1)
+        })
+    );
+}
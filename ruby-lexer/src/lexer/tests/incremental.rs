@@ -0,0 +1,121 @@
+use super::*;
+
+/// Lexes the whole source while recovering from errors, returning its spanned tokens
+fn lex_spanned(source: &str) -> Vec<SpannedToken<'_>> {
+    Lexer::new(source)
+        .with_error_recovery()
+        .map(|x| x.unwrap())
+        .collect()
+}
+
+/// Applies the edit to an incremental lexer, checking its tokens against lexing the whole source
+fn check_edit(lexer: &mut IncrementalLexer, start: usize, end: usize, text: &str) -> TokenDelta {
+    let delta = lexer.edit(start..end, text);
+    assert_eq!(
+        lexer.tokens(),
+        &lex_spanned(lexer.source())[..],
+        "after replacing {}..{} with {:?} in {:?}",
+        start,
+        end,
+        text,
+        lexer.source()
+    );
+    delta
+}
+
+#[test]
+fn resumes_from_checkpoint() {
+    enable_logging();
+    let source = "x = <<~A + \"b#{c}\"\n  one\n  A\nfoo(1, [2,\n3])\n";
+    let mut lexer = Lexer::new(source);
+    while lexer.get_pos().line() < 3 {
+        lexer.next().unwrap().unwrap();
+    }
+    let checkpoint = lexer.checkpoint();
+    let rest: Vec<_> = lexer.by_ref().collect();
+
+    let mut other = Lexer::new(source);
+    assert_ne!(other.checkpoint(), checkpoint);
+    other.restore(checkpoint.clone());
+    assert_eq!(other.checkpoint(), checkpoint);
+    assert_eq!(other.collect::<Vec<_>>(), rest);
+}
+
+#[test]
+fn matches_full_lexing() {
+    enable_logging();
+    let sources = [
+        "foo(1, 2)\nbar = <<~A + 'x'\n  one\n    #{two}\n  A\nwhile a do\n  b\nend\n",
+        "%w[a\nb] /c\n/i\n=begin\ndoc\n=end\nx ? y : z\r\n-> (a) { a }\n",
+        "def a\n  \"b #{c\n}\"\nend\n__END__\ndata\n",
+        "a\n  # b\n\n  .c\nd\n&.e\n..f\n",
+    ];
+    let snippets = [
+        "", "x", " ", "\n", "\"", "#", "<<A\n", "=begin\n", "do\n", "\\\n", "}", ".",
+    ];
+    for source in sources.iter() {
+        for start in (0..=source.len()).filter(|i| source.is_char_boundary(*i)) {
+            for end in start..(start + 3).min(source.len() + 1) {
+                for snippet in snippets.iter() {
+                    let mut lexer = IncrementalLexer::new(source.to_string());
+                    check_edit(&mut lexer, start, end, snippet);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn successive_edits() {
+    enable_logging();
+    let mut lexer = IncrementalLexer::new("a = 1\nb = [\n  2\n]\n".to_owned());
+    check_edit(&mut lexer, 11, 11, "\"");
+    check_edit(&mut lexer, 0, 0, "# comment\n");
+    check_edit(&mut lexer, 22, 23, "");
+    check_edit(&mut lexer, 4, 4, "x <<B\nB\n");
+    let len = lexer.source().len();
+    check_edit(&mut lexer, 0, len, "");
+    check_edit(&mut lexer, 0, 0, "c\n");
+}
+
+#[test]
+fn local_deltas() {
+    enable_logging();
+    let source = "foo(1)\n".repeat(50);
+    let mut lexer = IncrementalLexer::new(source);
+    let tokens_per_line = 5;
+
+    // Only the edited token changes
+    let delta = check_edit(&mut lexer, 7 * 10 + 4, 7 * 10 + 5, "22");
+    assert_eq!(
+        delta,
+        TokenDelta {
+            start: 10 * tokens_per_line + 2,
+            removed: 1,
+            inserted: 1,
+        }
+    );
+    assert_eq!(lexer.tokens()[delta.start].1, Token::Integer { value: 22 });
+
+    // An unterminated string swallows the remaining lines
+    let delta = check_edit(&mut lexer, 7 * 20 + 1, 7 * 20 + 1, "'");
+    assert_eq!(
+        delta,
+        TokenDelta {
+            start: 20 * tokens_per_line,
+            removed: 30 * tokens_per_line,
+            inserted: 1,
+        }
+    );
+
+    // Closing it splits the error into a string and the remaining lines
+    let delta = check_edit(&mut lexer, 7 * 30 + 4, 7 * 30 + 4, "'");
+    assert_eq!(
+        delta,
+        TokenDelta {
+            start: 20 * tokens_per_line,
+            removed: 1,
+            inserted: 1 + 20 * tokens_per_line,
+        }
+    );
+}
@@ -0,0 +1,242 @@
+use super::*;
+
+fn ident(value: &str) -> Token<'_> {
+    Token::Identifier {
+        value: value.into(),
+    }
+}
+
+fn ivar(value: &str) -> Token<'_> {
+    Token::InstanceVariable {
+        value: value.into(),
+    }
+}
+
+#[test]
+fn reserved_words() {
+    enable_logging();
+    let keywords = vec![
+        ("__LINE__", Token::KwLINE),
+        ("__ENCODING__", Token::KwENCODING),
+        ("__FILE__", Token::KwFILE),
+        ("BEGIN", Token::KwBEGIN),
+        ("END", Token::KwEND),
+        ("alias", Token::KwAlias),
+        ("begin", Token::KwBegin),
+        ("break", Token::KwBreak),
+        ("case", Token::KwCase),
+        ("class", Token::KwClass),
+        ("def", Token::KwDef),
+        ("defined?", Token::KwDefined),
+        ("else", Token::KwElse),
+        ("elsif", Token::KwElsif),
+        ("end", Token::KwEnd),
+        ("ensure", Token::KwEnsure),
+        ("false", Token::KwFalse),
+        ("for", Token::KwFor),
+        ("if", Token::KwIf),
+        ("in", Token::KwIn),
+        ("module", Token::KwModule),
+        ("next", Token::KwNext),
+        ("nil", Token::KwNil),
+        ("not", Token::KwNot),
+        ("redo", Token::KwRedo),
+        ("rescue", Token::KwRescue),
+        ("retry", Token::KwRetry),
+        ("return", Token::KwReturn),
+        ("self", Token::KwSelf),
+        ("super", Token::KwSuper),
+        ("then", Token::KwThen),
+        ("true", Token::KwTrue),
+        ("undef", Token::KwUndef),
+        ("unless", Token::KwUnless),
+        ("until", Token::KwUntil),
+        ("when", Token::KwWhen),
+        ("while", Token::KwWhile),
+        ("yield", Token::KwYield),
+    ];
+    for (source, token) in keywords {
+        assert_eq!(lex_source(source), Ok(vec![token]));
+    }
+    assert_eq!(
+        lex_tokens("@a and @b or not @c"),
+        vec![
+            ivar("@a"),
+            Token::KwAnd,
+            ivar("@b"),
+            Token::KwOr,
+            Token::KwNot,
+            ivar("@c")
+        ]
+    );
+}
+
+#[test]
+fn modifier_keywords() {
+    enable_logging();
+    assert_eq!(
+        lex_tokens("foo if bar"),
+        vec![ident("foo"), Token::KwIfModifier, ident("bar")]
+    );
+    assert_eq!(
+        lex_tokens("foo unless bar"),
+        vec![ident("foo"), Token::KwUnlessModifier, ident("bar")]
+    );
+    assert_eq!(
+        lex_tokens("foo while bar"),
+        vec![ident("foo"), Token::KwWhileModifier, ident("bar")]
+    );
+    assert_eq!(
+        lex_tokens("foo until bar"),
+        vec![ident("foo"), Token::KwUntilModifier, ident("bar")]
+    );
+    assert_eq!(
+        lex_tokens("foo rescue bar"),
+        vec![ident("foo"), Token::KwRescueModifier, ident("bar")]
+    );
+    assert_eq!(
+        lex_tokens("return if @a"),
+        vec![Token::KwReturn, Token::KwIfModifier, ivar("@a")]
+    );
+    assert_eq!(
+        lex_tokens("if @a then @b end"),
+        vec![
+            Token::KwIf,
+            ivar("@a"),
+            Token::KwThen,
+            ivar("@b"),
+            Token::KwEnd
+        ]
+    );
+    assert_eq!(
+        lex_tokens("@a = if @b\n"),
+        vec![
+            ivar("@a"),
+            Token::OpAssign,
+            Token::KwIf,
+            ivar("@b"),
+            Token::LineTerminator
+        ]
+    );
+}
+
+#[test]
+fn keywords_as_method_names() {
+    enable_logging();
+    assert_eq!(
+        lex_tokens("foo.if.end"),
+        vec![
+            ident("foo"),
+            Token::Dot,
+            ident("if"),
+            Token::Dot,
+            ident("end")
+        ]
+    );
+    assert_eq!(lex_tokens("def end"), vec![Token::KwDef, ident("end")]);
+    assert_eq!(
+        lex_tokens("alias while until"),
+        vec![Token::KwAlias, ident("while"), Token::KwUntilModifier]
+    );
+}
+
+#[test]
+fn do_keywords() {
+    enable_logging();
+    assert_eq!(
+        lex_tokens("while @a do end"),
+        vec![
+            Token::KwWhile,
+            ivar("@a"),
+            Token::KwDoForCondition,
+            Token::KwEnd
+        ]
+    );
+    assert_eq!(
+        lex_tokens("until @a do end"),
+        vec![
+            Token::KwUntil,
+            ivar("@a"),
+            Token::KwDoForCondition,
+            Token::KwEnd
+        ]
+    );
+    assert_eq!(
+        lex_tokens("until foo(@a) do end"),
+        vec![
+            Token::KwUntil,
+            ident("foo"),
+            Token::LeftParen,
+            ivar("@a"),
+            Token::RightParen,
+            Token::KwDoForCondition,
+            Token::KwEnd
+        ]
+    );
+    assert_eq!(
+        lex_tokens("for @a in @b do end"),
+        vec![
+            Token::KwFor,
+            ivar("@a"),
+            Token::KwIn,
+            ivar("@b"),
+            Token::KwDoForCondition,
+            Token::KwEnd
+        ]
+    );
+    assert_eq!(
+        lex_tokens("foo do end"),
+        vec![ident("foo"), Token::KwDo, Token::KwEnd]
+    );
+    assert_eq!(
+        lex_tokens("foo @a do end"),
+        vec![ident("foo"), ivar("@a"), Token::KwDoForBlock, Token::KwEnd]
+    );
+    assert_eq!(
+        lex_tokens("foo.bar @a do end"),
+        vec![
+            ident("foo"),
+            Token::Dot,
+            ident("bar"),
+            ivar("@a"),
+            Token::KwDoForBlock,
+            Token::KwEnd
+        ]
+    );
+    assert_eq!(
+        lex_tokens("while @a\nfoo @b do end\nend"),
+        vec![
+            Token::KwWhile,
+            ivar("@a"),
+            Token::LineTerminator,
+            ident("foo"),
+            ivar("@b"),
+            Token::KwDoForBlock,
+            Token::KwEnd,
+            Token::LineTerminator,
+            Token::KwEnd
+        ]
+    );
+    assert_eq!(
+        lex_tokens("foo @a\nbar do end"),
+        vec![
+            ident("foo"),
+            ivar("@a"),
+            Token::LineTerminator,
+            ident("bar"),
+            Token::KwDo,
+            Token::KwEnd
+        ]
+    );
+    assert_eq!(
+        lex_tokens("->(a) do end"),
+        vec![
+            Token::Lambda,
+            Token::LeftParen,
+            ident("a"),
+            Token::RightParen,
+            Token::KwDoForLambda,
+            Token::KwEnd
+        ]
+    );
+}
@@ -0,0 +1,205 @@
+use super::*;
+
+fn label(value: &str) -> Token<'_> {
+    Token::LabelTag {
+        value: value.into(),
+    }
+}
+
+fn ident(value: &str) -> Token<'_> {
+    Token::Identifier {
+        value: value.into(),
+    }
+}
+
+#[test]
+fn hash_labels() {
+    enable_logging();
+    let tokens = lex_tokens("{foo: 1, Foo: :a, if: x,\n  end:\n  2}");
+    assert_eq!(
+        tokens,
+        vec![
+            Token::LeftBrace,
+            label("foo"),
+            Token::Integer { value: 1 },
+            Token::Comma,
+            label("Foo"),
+            Token::Symbol { value: "a".into() },
+            Token::Comma,
+            label("if"),
+            ident("x"),
+            Token::Comma,
+            Token::Newline,
+            label("end"),
+            Token::Newline,
+            Token::Integer { value: 2 },
+            Token::RightBrace,
+        ]
+    );
+}
+
+#[test]
+fn argument_labels() {
+    enable_logging();
+    let tokens = lex_tokens("foo a: -1, b: [2]\nbar(c: {}, d: /e/)\ndef x y:, z: 1; end");
+    assert_eq!(
+        tokens,
+        vec![
+            ident("foo"),
+            label("a"),
+            Token::Integer { value: -1 },
+            Token::Comma,
+            label("b"),
+            Token::LeftBracket,
+            Token::Integer { value: 2 },
+            Token::RightBracket,
+            Token::LineTerminator,
+            ident("bar"),
+            Token::LeftParen,
+            label("c"),
+            Token::LeftBrace,
+            Token::RightBrace,
+            Token::Comma,
+            label("d"),
+            Token::Regex {
+                value: "e".into(),
+                options: "".into()
+            },
+            Token::RightParen,
+            Token::LineTerminator,
+            Token::KwDef,
+            ident("x"),
+            label("y"),
+            Token::Comma,
+            label("z"),
+            Token::Integer { value: 1 },
+            Token::Semicolon,
+            Token::KwEnd,
+        ]
+    );
+}
+
+#[test]
+fn method_only_labels() {
+    enable_logging();
+    let tokens = lex_tokens("{a?: 1}\nfoo(b!: 2)");
+    assert_eq!(
+        tokens,
+        vec![
+            Token::LeftBrace,
+            label("a?"),
+            Token::Integer { value: 1 },
+            Token::RightBrace,
+            Token::LineTerminator,
+            ident("foo"),
+            Token::LeftParen,
+            label("b!"),
+            Token::Integer { value: 2 },
+            Token::RightParen,
+        ]
+    );
+}
+
+#[test]
+fn quoted_labels() {
+    enable_logging();
+    let tokens = lex_tokens("{\"quoted key\": 1, 'q': 2, \"a#{b}\": 3, \"c\" => 4}");
+    assert_eq!(
+        tokens,
+        vec![
+            Token::LeftBrace,
+            label("quoted key"),
+            Token::Integer { value: 1 },
+            Token::Comma,
+            label("q"),
+            Token::Integer { value: 2 },
+            Token::Comma,
+            Token::StringPart { value: "a".into() },
+            ident("b"),
+            label(""),
+            Token::Integer { value: 3 },
+            Token::Comma,
+            Token::String { value: "c".into() },
+            Token::Arrow,
+            Token::Integer { value: 4 },
+            Token::RightBrace,
+        ]
+    );
+}
+
+#[test]
+fn label_state() {
+    let mut lexer = Lexer::new("(a: b");
+    assert_eq!(lexer.next().unwrap().unwrap().1, Token::LeftParen);
+    assert_eq!(lexer.lex_state, LexState::EXPR_BEG | LexState::EXPR_LABEL);
+    let (start, token, end) = lexer.next().unwrap().unwrap();
+    assert_eq!(token, label("a"));
+    assert_eq!((start.offset(), end.offset()), (1, 3));
+    assert_eq!(lexer.lex_state, LexState::EXPR_ARG | LexState::EXPR_LABELED);
+}
+
+#[test]
+fn not_labels() {
+    enable_logging();
+    let tokens = lex_tokens("a ? b :c\nx ? \"y\":z\nfoo(A::B)");
+    assert_eq!(
+        tokens,
+        vec![
+            ident("a"),
+            Token::OpTernaryIf,
+            ident("b"),
+            Token::Symbol { value: "c".into() },
+            Token::LineTerminator,
+            ident("x"),
+            Token::OpTernaryIf,
+            Token::String { value: "y".into() },
+            Token::OpTernaryElse,
+            ident("z"),
+            Token::LineTerminator,
+            ident("foo"),
+            Token::LeftParen,
+            Token::Constant { value: "A".into() },
+            Token::DoubleColon,
+            Token::Constant { value: "B".into() },
+            Token::RightParen,
+        ]
+    );
+}
+
+#[test]
+fn keywords_after_labels() {
+    enable_logging();
+    let tokens =
+        lex_tokens("{a: if x then 1 end}\nfoo(a: while x do y end)\nbar b: unless z; 2 end");
+    assert_eq!(
+        tokens,
+        vec![
+            Token::LeftBrace,
+            label("a"),
+            Token::KwIf,
+            ident("x"),
+            Token::KwThen,
+            Token::Integer { value: 1 },
+            Token::KwEnd,
+            Token::RightBrace,
+            Token::LineTerminator,
+            ident("foo"),
+            Token::LeftParen,
+            label("a"),
+            Token::KwWhile,
+            ident("x"),
+            Token::KwDoForCondition,
+            ident("y"),
+            Token::KwEnd,
+            Token::RightParen,
+            Token::LineTerminator,
+            ident("bar"),
+            label("b"),
+            Token::KwUnless,
+            ident("z"),
+            Token::Semicolon,
+            Token::Integer { value: 2 },
+            Token::KwEnd,
+        ]
+    );
+}
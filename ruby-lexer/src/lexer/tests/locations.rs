@@ -0,0 +1,76 @@
+use super::*;
+
+/// Lexes the source string, returning the spans of its tokens
+fn lex_spans(source: &str) -> Vec<(Location, Token<'_>, Location)> {
+    make_tokenizer(source).map(|x| x.unwrap()).collect()
+}
+
+#[test]
+fn token_offsets() {
+    enable_logging();
+    let source = "foo = 1\nbar";
+    let spans = lex_spans(source);
+    let texts: Vec<&str> = spans
+        .iter()
+        .map(|(start, _, end)| &source[start.offset()..end.offset()])
+        .collect();
+    assert_eq!(texts, vec!["foo", " ", "=", " ", "1", "\n", "bar"]);
+    let (start, _, end) = &spans[6];
+    assert_eq!((start.line(), start.col()), (2, 1));
+    assert_eq!((start.offset(), start.char_offset()), (8, 8));
+    assert_eq!((end.line(), end.col()), (2, 4));
+    assert_eq!((end.offset(), end.char_offset()), (11, 11));
+}
+
+#[test]
+fn multibyte_columns() {
+    enable_logging();
+    let source = "ä = \"😀\" + ab";
+    let spans = lex_spans(source);
+    let texts: Vec<&str> = spans
+        .iter()
+        .map(|(start, _, end)| &source[start.offset()..end.offset()])
+        .collect();
+    assert_eq!(
+        texts,
+        vec!["ä", " ", "=", " ", "\"😀\"", " ", "+", " ", "ab"]
+    );
+
+    // The identifier following the multibyte characters
+    let (start, _, end) = &spans[8];
+    assert_eq!(start.col(), 11);
+    assert_eq!(start.byte_col(), 15);
+    assert_eq!(start.utf16_col(), 12);
+    assert_eq!(start.offset(), 14);
+    assert_eq!(start.char_offset(), 10);
+    assert_eq!((end.col(), end.byte_col(), end.utf16_col()), (13, 17, 14));
+}
+
+#[test]
+fn locations_from_source() {
+    let source = "ä\n😀b";
+    let location = Location::from_source(source, 2, 2).unwrap();
+    assert_eq!(location.line(), 2);
+    assert_eq!(location.col(), 2);
+    assert_eq!(location.byte_col(), 5);
+    assert_eq!(location.utf16_col(), 3);
+    assert_eq!(location.offset(), 7);
+    assert_eq!(location.char_offset(), 3);
+    assert_eq!(Location::from_source(source, 3, 1), None);
+    assert_eq!(Location::from_source(source, 1, 5), None);
+}
+
+#[test]
+fn windows_line_endings() {
+    enable_logging();
+    let source = "a\r\n\r\nb # c\r\n";
+    let spans = lex_spans(source);
+    let texts: Vec<&str> = spans
+        .iter()
+        .map(|(start, _, end)| &source[start.offset()..end.offset()])
+        .collect();
+    assert_eq!(texts, vec!["a", "\r\n", "\r\n", "b", " ", "# c", "\r\n"]);
+    let (start, _, _) = &spans[3];
+    assert_eq!(start, &location(source, 3, 1));
+    assert_eq!((start.offset(), start.char_offset()), (5, 5));
+}
@@ -0,0 +1,113 @@
+use super::*;
+
+/// Lexes the source in lossless mode
+fn lex_lossless(source: &str) -> Vec<LosslessToken<'_>> {
+    Lexer::new(source)
+        .into_lossless()
+        .map(|x| x.unwrap())
+        .collect()
+}
+
+fn trivia(kind: TriviaKind, text: &str) -> Trivia<'_> {
+    Trivia {
+        kind,
+        text: text.into(),
+    }
+}
+
+#[test]
+fn round_trips() {
+    enable_logging();
+    let sources = [
+        "",
+        "foo = 1\r\nbar(2,\r\n  3)\r\n",
+        "a = b \\\n  + c \\\r\n  + d # sum\n",
+        "x = <<~A + <<-'B'\r\n    one #{two}\r\n  three\r\n  A\r\nfour\r\n  B\r\n",
+        "# comment\n\n=begin\ndoc\n=end rest\r\nfoo\n# trailing",
+        "%w[a b\\ c] :\"s\" /r#{e}/i ?a $1 @@b -> (x) { x }\t\n",
+        "  \n\t# only trivia\n  ",
+    ];
+    for source in sources.iter() {
+        let tokens = lex_lossless(source);
+        let text: String = tokens.iter().map(|token| token.to_string()).collect();
+        assert_eq!(&text, source);
+        assert_eq!(tokens.last().unwrap().token, Token::EndOfFile);
+    }
+}
+
+#[test]
+fn round_trips_with_error_recovery() {
+    enable_logging();
+    let source = "a = 0x + \"b#{ $ }\"\nc = 1\n'unterminated";
+    let text: String = Lexer::new(source)
+        .with_error_recovery()
+        .into_lossless()
+        .map(|token| token.unwrap().to_string())
+        .collect();
+    assert_eq!(text, source);
+}
+
+#[test]
+fn attaches_trivia() {
+    enable_logging();
+    let tokens = lex_lossless("# a\nfoo  # b\r\n\n  bar \\\n baz\n");
+    let summary: Vec<(&str, Vec<Trivia>, Vec<Trivia>)> = tokens
+        .iter()
+        .map(|token| {
+            (
+                token.text.as_ref(),
+                token.leading_trivia.clone(),
+                token.trailing_trivia.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "foo",
+                vec![
+                    trivia(TriviaKind::Comment, "# a"),
+                    trivia(TriviaKind::Newline, "\n")
+                ],
+                vec![
+                    trivia(TriviaKind::Whitespace, "  "),
+                    trivia(TriviaKind::Comment, "# b")
+                ]
+            ),
+            ("\r\n", vec![], vec![]),
+            (
+                "bar",
+                vec![
+                    trivia(TriviaKind::Newline, "\n"),
+                    trivia(TriviaKind::Whitespace, "  ")
+                ],
+                vec![
+                    trivia(TriviaKind::Whitespace, " "),
+                    trivia(TriviaKind::LineContinuation, "\\\n"),
+                    trivia(TriviaKind::Whitespace, " ")
+                ]
+            ),
+            ("baz", vec![], vec![]),
+            ("\n", vec![], vec![]),
+            ("", vec![], vec![]),
+        ]
+    );
+    assert_eq!(tokens[1].token, Token::LineTerminator);
+    assert_eq!(tokens[2].start, location("# a\nfoo  # b\r\n\n  bar", 4, 3));
+}
+
+#[test]
+fn lexical_errors() {
+    enable_logging();
+    let results: Vec<_> = Lexer::new("foo 'bar").into_lossless().collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap().text, "foo");
+    assert_eq!(
+        results[1],
+        Err(LexicalError {
+            message: "unterminated string meets end of file".to_owned(),
+            location: location("foo 'bar", 1, 5),
+        })
+    );
+}
@@ -0,0 +1,100 @@
+use super::*;
+
+/// Lexes the source string, returning the values set by its magic comments and the kinds of
+/// the warnings reported
+fn lex_magic_comments(source: &str) -> (MagicComments, Vec<WarningKind>) {
+    let mut lexer = Lexer::new(source).with_warning_level(WarningLevel::Verbose);
+    for result in lexer.by_ref() {
+        result.unwrap();
+    }
+    let warnings = lexer.take_warnings().iter().map(|w| w.kind).collect();
+    (lexer.magic_comments().clone(), warnings)
+}
+
+fn encoding(source: &str) -> Option<String> {
+    lex_magic_comments(source).0.encoding
+}
+
+#[test]
+fn encoding_comments() {
+    enable_logging();
+    assert_eq!(encoding("# encoding: utf-8"), Some("utf-8".to_owned()));
+    assert_eq!(
+        encoding("# coding: Shift_JIS\nfoo"),
+        Some("Shift_JIS".to_owned())
+    );
+    assert_eq!(encoding("# Encoding : euc-jp"), Some("euc-jp".to_owned()));
+    assert_eq!(
+        encoding("# -*- coding: utf-8 -*-"),
+        Some("utf-8".to_owned())
+    );
+    assert_eq!(
+        encoding("# -*- mode: ruby; coding: iso-8859-1 -*-"),
+        Some("iso-8859-1".to_owned())
+    );
+    assert_eq!(
+        encoding("# vim: set fileencoding=utf-8 :"),
+        Some("utf-8".to_owned())
+    );
+    assert_eq!(
+        encoding("#!/usr/bin/env ruby\n# encoding: ascii-8bit"),
+        Some("ascii-8bit".to_owned())
+    );
+
+    // Encoding comments are only recognized at the top of the file
+    assert_eq!(encoding("\n# encoding: utf-8"), None);
+    assert_eq!(encoding("# foo\n# encoding: utf-8"), None);
+    assert_eq!(encoding("foo # encoding: utf-8"), None);
+    assert_eq!(encoding("# just a comment"), None);
+}
+
+#[test]
+fn frozen_string_literal_comments() {
+    enable_logging();
+    let (magic, warnings) = lex_magic_comments("# frozen_string_literal: true\nfoo");
+    assert_eq!(magic.frozen_string_literal, Some(true));
+    assert_eq!(warnings, vec![]);
+    let (magic, _) = lex_magic_comments("# encoding: utf-8\n\n# Frozen-String-Literal: FALSE\n");
+    assert_eq!(magic.frozen_string_literal, Some(false));
+    let (magic, _) = lex_magic_comments("# -*- frozen_string_literal: true; coding: utf-8 -*-");
+    assert_eq!(magic.frozen_string_literal, Some(true));
+    assert_eq!(magic.encoding, Some("utf-8".to_owned()));
+
+    // The comment must precede the first token
+    let (magic, warnings) = lex_magic_comments("foo\n# frozen_string_literal: true");
+    assert_eq!(magic.frozen_string_literal, None);
+    assert_eq!(warnings, vec![WarningKind::IgnoredMagicComment]);
+
+    let (magic, warnings) = lex_magic_comments("# frozen_string_literal: maybe");
+    assert_eq!(magic.frozen_string_literal, None);
+    assert_eq!(warnings, vec![WarningKind::InvalidMagicCommentValue]);
+}
+
+#[test]
+fn warn_indent_comments() {
+    enable_logging();
+    let (magic, _) = lex_magic_comments("# warn_indent: true\nfoo\n# warn_indent: false\n");
+    assert_eq!(magic.warn_indent, Some(false));
+}
+
+#[test]
+fn shareable_constant_value_comments() {
+    enable_logging();
+    let (magic, warnings) = lex_magic_comments(
+        "# shareable_constant_value: literal\nA = 1\n# shareable_constant_value: experimental_copy\n",
+    );
+    assert_eq!(
+        magic.shareable_constant_value,
+        Some(ShareableConstantValue::ExperimentalCopy)
+    );
+    assert_eq!(warnings, vec![]);
+
+    // The comment must be on a line of its own
+    let (magic, warnings) = lex_magic_comments("A = 1 # shareable_constant_value: none");
+    assert_eq!(magic.shareable_constant_value, None);
+    assert_eq!(warnings, vec![WarningKind::IgnoredMagicComment]);
+
+    let (magic, warnings) = lex_magic_comments("# shareable_constant_value: all");
+    assert_eq!(magic.shareable_constant_value, None);
+    assert_eq!(warnings, vec![WarningKind::InvalidMagicCommentValue]);
+}
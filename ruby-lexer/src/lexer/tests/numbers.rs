@@ -1,10 +1,24 @@
 use super::*;
+use num_bigint::BigInt;
 
-fn integer(value: isize) -> Result<Vec<Token>, LexicalError> {
+fn integer(value: isize) -> Result<Vec<Token<'static>>, LexicalError> {
     Ok(vec![Token::Integer { value: value }])
 }
 
-fn float(value: f64) -> Result<Vec<Token>, LexicalError> {
+fn big_integer(value: &str, radix: u32) -> Result<Vec<Token<'static>>, LexicalError> {
+    Ok(vec![Token::BigInteger {
+        value: BigInt::parse_bytes(value.as_bytes(), radix).unwrap(),
+    }])
+}
+
+fn rational(numerator: isize, denominator: isize) -> Result<Vec<Token<'static>>, LexicalError> {
+    Ok(vec![Token::Rational {
+        numerator: BigInt::from(numerator),
+        denominator: BigInt::from(denominator),
+    }])
+}
+
+fn float(value: f64) -> Result<Vec<Token<'static>>, LexicalError> {
     Ok(vec![Token::Float { value: value }])
 }
 
@@ -85,7 +99,126 @@ fn hexadecimal() {
     assert_eq!(lex_source("+0x2921_B4"), integer(2695604));
 }
 
+#[test]
+fn big_integers() {
+    enable_logging();
+    assert_eq!(lex_source("9223372036854775807"), integer(isize::MAX));
+    assert_eq!(lex_source("-9223372036854775808"), integer(isize::MIN));
+    assert_eq!(
+        lex_source("9223372036854775808"),
+        big_integer("9223372036854775808", 10)
+    );
+    assert_eq!(
+        lex_source("-123_456_789_012_345_678_901_234_567_890"),
+        big_integer("-123456789012345678901234567890", 10)
+    );
+    assert_eq!(
+        lex_source("0xFFFF_FFFF_FFFF_FFFF_FFFF"),
+        big_integer("FFFFFFFFFFFFFFFFFFFF", 16)
+    );
+    assert_eq!(
+        lex_source("0b1111111111111111111111111111111111111111111111111111111111111111"),
+        big_integer("FFFFFFFFFFFFFFFF", 16)
+    );
+    assert_eq!(
+        lex_source("0o1777777777777777777777"),
+        big_integer("FFFFFFFFFFFFFFFF", 16)
+    );
+}
+
+#[test]
+fn malformed_numbers() {
+    enable_logging();
+    let error = |source, message: &str, col| {
+        Err(LexicalError {
+            message: message.to_owned(),
+            location: location(source, 1, col),
+        })
+    };
+    assert_eq!(
+        lex_source("0x"),
+        error("0x", "numeric literal without digits", 3)
+    );
+    assert_eq!(
+        lex_source("0b2"),
+        error("0b2", "numeric literal without digits", 3)
+    );
+    assert_eq!(
+        lex_source("0o8"),
+        error("0o8", "numeric literal without digits", 3)
+    );
+    assert_eq!(
+        lex_source("0d_1"),
+        error("0d_1", "numeric literal without digits", 3)
+    );
+    assert_eq!(lex_source("08"), error("08", "Invalid octal digit", 2));
+    assert_eq!(lex_source("0b12"), error("0b12", "Invalid binary digit", 4));
+    assert_eq!(lex_source("0o78"), error("0o78", "Invalid octal digit", 4));
+    assert_eq!(lex_source("0_19"), error("0_19", "Invalid octal digit", 4));
+    assert_eq!(lex_source("1_"), error("1_", "trailing '_' in number", 3));
+    assert_eq!(
+        lex_source("1__2"),
+        error("1__2", "trailing '_' in number", 3)
+    );
+    assert_eq!(lex_source("0_"), error("0_", "trailing '_' in number", 3));
+}
+
+#[test]
+fn rationals() {
+    enable_logging();
+    assert_eq!(lex_source("3r"), rational(3, 1));
+    assert_eq!(lex_source("0r"), rational(0, 1));
+    assert_eq!(lex_source("-3r"), rational(-3, 1));
+    assert_eq!(lex_source("0x10r"), rational(16, 1));
+    assert_eq!(lex_source("0b11r"), rational(3, 1));
+    assert_eq!(lex_source("1.5r"), rational(3, 2));
+    assert_eq!(lex_source("-0.25r"), rational(-1, 4));
+    assert_eq!(lex_source("0.1r"), rational(1, 10));
+    assert_eq!(
+        lex_source("123456789012345678901234567890r"),
+        Ok(vec![Token::Rational {
+            numerator: BigInt::parse_bytes(b"123456789012345678901234567890", 10).unwrap(),
+            denominator: BigInt::from(1),
+        }])
+    );
+}
+
+#[test]
+fn imaginaries() {
+    enable_logging();
+    let imaginary = |token: Result<Vec<Token<'static>>, LexicalError>| {
+        Ok(vec![Token::Imaginary {
+            value: Box::new(token.unwrap().remove(0)),
+        }])
+    };
+    assert_eq!(lex_source("2i"), imaginary(integer(2)));
+    assert_eq!(lex_source("0i"), imaginary(integer(0)));
+    assert_eq!(lex_source("0x10i"), imaginary(integer(16)));
+    assert_eq!(lex_source("1.5i"), imaginary(float(1.5)));
+    assert_eq!(lex_source("1e3i"), imaginary(float(1000.0)));
+    assert_eq!(lex_source("3ri"), imaginary(rational(3, 1)));
+    assert_eq!(lex_source("1.5ri"), imaginary(rational(3, 2)));
+}
+
+#[test]
+fn invalid_number_suffixes() {
+    enable_logging();
+    let error = |source, col| {
+        Err(LexicalError {
+            message: "syntax error, unexpected local variable or method".to_owned(),
+            location: location(source, 1, col),
+        })
+    };
+    assert_eq!(lex_source("1e3r"), error("1e3r", 4));
+    assert_eq!(lex_source("2ir"), error("2ir", 2));
+    assert_eq!(lex_source("3rr"), error("3rr", 2));
+    assert_eq!(lex_source("4rx"), error("4rx", 2));
+    assert_eq!(lex_source("5abc"), error("5abc", 2));
+    assert_eq!(
+        lex_source("1if"),
+        Ok(vec![Token::Integer { value: 1 }, Token::KwIfModifier])
+    );
+}
+
 // TODO
-// test max & min for floats and integers
-// test bigint stuff
-// test lexical errors
+// test max & min for floats
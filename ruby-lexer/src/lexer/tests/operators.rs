@@ -0,0 +1,201 @@
+use super::*;
+
+fn ident(value: &str) -> Token<'_> {
+    Token::Identifier {
+        value: value.into(),
+    }
+}
+
+fn assign(value: &str) -> Token<'_> {
+    Token::AssignmentOperator {
+        value: value.into(),
+    }
+}
+
+#[test]
+fn binary_operators() {
+    enable_logging();
+    let operators = vec![
+        ("<", Token::OpLt),
+        ("<=", Token::OpLtEqual),
+        ("<=>", Token::OpCompare),
+        ("<<", Token::OpLeftShift),
+        ("&&", Token::OpAnd),
+        ("||", Token::OpOr),
+        ("&", Token::OpBinAnd),
+        ("|", Token::OpBinOr),
+        ("^", Token::OpBinXor),
+        ("+", Token::OpPlus),
+        ("-", Token::OpMinus),
+        ("*", Token::OpMultiply),
+        ("**", Token::OpExponent),
+    ];
+    for (op, token) in operators {
+        for source in &[format!("a {} b", op), format!("a{}b", op)] {
+            assert_eq!(
+                lex_tokens(source),
+                vec![ident("a"), token.clone(), ident("b")]
+            );
+        }
+    }
+    assert_eq!(
+        lex_tokens("1 + 2 - 3"),
+        vec![
+            Token::Integer { value: 1 },
+            Token::OpPlus,
+            Token::Integer { value: 2 },
+            Token::OpMinus,
+            Token::Integer { value: 3 }
+        ]
+    );
+    assert_eq!(
+        lex_tokens("a-1"),
+        vec![ident("a"), Token::OpMinus, Token::Integer { value: 1 }]
+    );
+}
+
+#[test]
+fn compound_assignments() {
+    enable_logging();
+    for op in &[
+        "<<=", "&&=", "||=", "&=", "|=", "^=", "+=", "-=", "*=", "**=",
+    ] {
+        assert_eq!(
+            lex_tokens(&format!("a {} -1", op)),
+            vec![ident("a"), assign(op), Token::Integer { value: -1 }]
+        );
+    }
+}
+
+#[test]
+fn unary_operators() {
+    enable_logging();
+    assert_eq!(lex_tokens("-a"), vec![Token::OpUnaryMinus, ident("a")]);
+    assert_eq!(lex_tokens("+a"), vec![Token::OpUnaryPlus, ident("a")]);
+    assert_eq!(lex_tokens("-1"), vec![Token::Integer { value: -1 }]);
+    assert_eq!(
+        lex_tokens("a = -b"),
+        vec![ident("a"), Token::OpAssign, Token::OpUnaryMinus, ident("b")]
+    );
+    // Ambiguous arguments are treated as unary
+    assert_eq!(
+        lex_tokens("foo -b"),
+        vec![ident("foo"), Token::OpUnaryMinus, ident("b")]
+    );
+    assert_eq!(
+        lex_tokens("foo +1"),
+        vec![ident("foo"), Token::Integer { value: 1 }]
+    );
+    assert_eq!(
+        lex_tokens("foo - b"),
+        vec![ident("foo"), Token::OpMinus, ident("b")]
+    );
+}
+
+#[test]
+fn splats() {
+    enable_logging();
+    assert_eq!(
+        lex_tokens("foo *a, **b"),
+        vec![
+            ident("foo"),
+            Token::Star,
+            ident("a"),
+            Token::Comma,
+            Token::TwoStar,
+            ident("b")
+        ]
+    );
+    assert_eq!(
+        lex_tokens("*a = b"),
+        vec![Token::Star, ident("a"), Token::OpAssign, ident("b")]
+    );
+    assert_eq!(
+        lex_tokens("def *(a)"),
+        vec![
+            Token::KwDef,
+            Token::OpMultiply,
+            Token::LeftParen,
+            ident("a"),
+            Token::RightParen
+        ]
+    );
+}
+
+#[test]
+fn ampersands() {
+    enable_logging();
+    assert_eq!(
+        lex_tokens("a&.b"),
+        vec![ident("a"), Token::SafeNavigation, ident("b")]
+    );
+    assert_eq!(
+        lex_tokens("a&.end"),
+        vec![ident("a"), Token::SafeNavigation, ident("end")]
+    );
+    assert_eq!(
+        lex_tokens("foo &blk"),
+        vec![ident("foo"), Token::Ampersand, ident("blk")]
+    );
+    assert_eq!(
+        lex_tokens("foo & blk"),
+        vec![ident("foo"), Token::OpBinAnd, ident("blk")]
+    );
+}
+
+#[test]
+fn lambdas() {
+    enable_logging();
+    assert_eq!(
+        lex_tokens("-> do end"),
+        vec![Token::Lambda, Token::KwDoForLambda, Token::KwEnd]
+    );
+    assert_eq!(
+        lex_tokens("-> a do end"),
+        vec![
+            Token::Lambda,
+            ident("a"),
+            Token::KwDoForLambda,
+            Token::KwEnd
+        ]
+    );
+}
+
+#[test]
+fn block_parameters() {
+    enable_logging();
+    assert_eq!(
+        lex_tokens("foo do |a| end"),
+        vec![
+            ident("foo"),
+            Token::KwDo,
+            Token::OpBinOr,
+            ident("a"),
+            Token::OpBinOr,
+            Token::KwEnd
+        ]
+    );
+    assert_eq!(
+        lex_tokens("foo do || end"),
+        vec![
+            ident("foo"),
+            Token::KwDo,
+            Token::OpBinOr,
+            Token::OpBinOr,
+            Token::KwEnd
+        ]
+    );
+}
+
+#[test]
+fn operator_symbols() {
+    enable_logging();
+    for op in &["<", "<=", "<=>", "<<", "&", "|", "^"] {
+        assert_eq!(
+            lex_source(&format!(":{}", op)),
+            Ok(vec![Token::Symbol {
+                value: (*op).into()
+            }])
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use super::*;
+
+fn string(value: &str) -> Token<'_> {
+    Token::String {
+        value: value.into(),
+    }
+}
+
+fn symbol(value: &str) -> Token<'_> {
+    Token::Symbol {
+        value: value.into(),
+    }
+}
+
+#[test]
+fn percent_strings() {
+    enable_logging();
+    assert_eq!(lex_source("%q(a (b) c)"), Ok(vec![string("a (b) c")]));
+    assert_eq!(lex_source("%q[it\\]s]"), Ok(vec![string("it]s")]));
+    assert_eq!(lex_source("%q<\\<a\\n>"), Ok(vec![string("<a\\n")]));
+    assert_eq!(lex_source("%Q{a\\tb}"), Ok(vec![string("a\tb")]));
+    assert_eq!(lex_source("%(foo)"), Ok(vec![string("foo")]));
+    assert_eq!(lex_source("%|a'b\"c|"), Ok(vec![string("a'b\"c")]));
+    assert_eq!(
+        lex_source("%Q{a{#{@b}}}"),
+        Ok(vec![
+            Token::StringPart { value: "a{".into() },
+            Token::InstanceVariable { value: "@b".into() },
+            string("}")
+        ])
+    );
+}
+
+#[test]
+fn percent_word_lists() {
+    enable_logging();
+    assert_eq!(
+        lex_source("%w(a  b\n\tc)"),
+        Ok(vec![
+            Token::WordsStart,
+            string("a"),
+            string("b"),
+            string("c"),
+            Token::ListEnd
+        ])
+    );
+    assert_eq!(
+        lex_source("%w[ ]"),
+        Ok(vec![Token::WordsStart, Token::ListEnd])
+    );
+    assert_eq!(
+        lex_source("%w(a\\ b (c d) \\#{e}\\n)"),
+        Ok(vec![
+            Token::WordsStart,
+            string("a b"),
+            string("(c"),
+            string("d)"),
+            string("\\#{e}\\n"),
+            Token::ListEnd
+        ])
+    );
+    assert_eq!(
+        lex_source("%W<a#{@b}c\\td #{@e}>"),
+        Ok(vec![
+            Token::WordsStart,
+            Token::StringPart { value: "a".into() },
+            Token::InstanceVariable { value: "@b".into() },
+            string("c\td"),
+            Token::StringPart { value: "".into() },
+            Token::InstanceVariable { value: "@e".into() },
+            string(""),
+            Token::ListEnd
+        ])
+    );
+}
+
+#[test]
+fn percent_symbol_lists() {
+    enable_logging();
+    assert_eq!(
+        lex_source("%i(a b)"),
+        Ok(vec![
+            Token::SymbolsStart,
+            symbol("a"),
+            symbol("b"),
+            Token::ListEnd
+        ])
+    );
+    assert_eq!(
+        lex_source("%I[a#{@b}]"),
+        Ok(vec![
+            Token::SymbolsStart,
+            Token::StringPart { value: "a".into() },
+            Token::InstanceVariable { value: "@b".into() },
+            symbol(""),
+            Token::ListEnd
+        ])
+    );
+}
+
+#[test]
+fn other_percent_literals() {
+    enable_logging();
+    assert_eq!(lex_source("%s(foo bar)"), Ok(vec![symbol("foo bar")]));
+    assert_eq!(
+        lex_source("%x(ls -la)"),
+        Ok(vec![Token::XString {
+            value: "ls -la".into()
+        }])
+    );
+    assert_eq!(
+        lex_source("`echo \\\"#{@a}\\\"`"),
+        Ok(vec![
+            Token::StringPart {
+                value: "echo \"".into()
+            },
+            Token::InstanceVariable { value: "@a".into() },
+            Token::XString { value: "\"".into() }
+        ])
+    );
+    assert_eq!(
+        lex_source("%r{\\d+/\\}}"),
+        Ok(vec![Token::Regex {
+            value: "\\d+/\\}".into(),
+            options: "".into()
+        }])
+    );
+    assert_eq!(
+        lex_source("%r!a\\!b!"),
+        Ok(vec![Token::Regex {
+            value: "a!b".into(),
+            options: "".into()
+        }])
+    );
+}
+
+#[test]
+fn modulus_operator() {
+    enable_logging();
+    assert_eq!(
+        lex_source("@a % @b"),
+        Ok(vec![
+            Token::InstanceVariable { value: "@a".into() },
+            Token::Whitespace,
+            Token::OpModulus,
+            Token::Whitespace,
+            Token::InstanceVariable { value: "@b".into() }
+        ])
+    );
+    assert_eq!(
+        lex_source("@a%=2"),
+        Ok(vec![
+            Token::InstanceVariable { value: "@a".into() },
+            Token::AssignmentOperator { value: "%=".into() },
+            Token::Integer { value: 2 }
+        ])
+    );
+}
+
+#[test]
+fn percent_literal_errors() {
+    enable_logging();
+    assert_eq!(
+        lex_source("%z(a)"),
+        Err(LexicalError {
+            message: "unknown type of %string".to_owned(),
+            location: location("%z(a)", 1, 1)
+        })
+    );
+    assert_eq!(
+        lex_source("%wa"),
+        Err(LexicalError {
+            message: "unknown type of %string".to_owned(),
+            location: location("%wa", 1, 1)
+        })
+    );
+    assert_eq!(
+        lex_source("%"),
+        Err(LexicalError {
+            message: "unterminated quoted string meets end of file".to_owned(),
+            location: location("%", 1, 1)
+        })
+    );
+    assert_eq!(
+        lex_source("%w(a b"),
+        Err(LexicalError {
+            message: "unterminated list meets end of file".to_owned(),
+            location: location("%w(a b", 1, 1)
+        })
+    );
+    assert_eq!(
+        lex_source("%q(a (b)"),
+        Err(LexicalError {
+            message: "unterminated string meets end of file".to_owned(),
+            location: location("%q(a (b)", 1, 1)
+        })
+    );
+}
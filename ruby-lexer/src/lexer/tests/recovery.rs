@@ -0,0 +1,156 @@
+use super::*;
+
+/// Lexes the source string while recovering from errors, returning its tokens other than
+/// whitespace along with the errors encountered
+fn lex_recovering(source: &str) -> (Vec<Token<'_>>, Vec<LexicalError>) {
+    let mut lexer = Lexer::new(source).with_error_recovery();
+    let tokens = lexer
+        .by_ref()
+        .map(|x| x.unwrap().1)
+        .filter(|token| *token != Token::Whitespace)
+        .collect();
+    (tokens, lexer.take_errors())
+}
+
+fn ident(value: &str) -> Token<'_> {
+    Token::Identifier {
+        value: value.into(),
+    }
+}
+
+fn error(source: &str, message: &str, line: usize, col: usize) -> LexicalError {
+    LexicalError {
+        message: message.to_owned(),
+        location: location(source, line, col),
+    }
+}
+
+#[test]
+fn recovery_is_opt_in() {
+    enable_logging();
+    let mut lexer = Lexer::new("0x");
+    assert!(lexer.next().unwrap().is_err());
+    assert!(lexer.errors().is_empty());
+}
+
+#[test]
+fn recover_at_end_of_line() {
+    enable_logging();
+    let source = "foo 0x + 1\nbar $-\nbaz";
+    assert_eq!(
+        lex_recovering(source),
+        (
+            vec![
+                ident("foo"),
+                Token::Error,
+                Token::LineTerminator,
+                ident("bar"),
+                Token::Error,
+                Token::LineTerminator,
+                ident("baz")
+            ],
+            vec![
+                error(source, "numeric literal without digits", 1, 7),
+                error(
+                    source,
+                    "`$-' is not allowed as a global variable name",
+                    2,
+                    5
+                )
+            ]
+        )
+    );
+}
+
+#[test]
+fn recover_at_closing_delimiter() {
+    enable_logging();
+    let source = "foo(1_, bar)]\nbaz";
+    assert_eq!(
+        lex_recovering(source),
+        (
+            vec![
+                ident("foo"),
+                Token::LeftParen,
+                Token::Error,
+                Token::RightParen,
+                Token::Error,
+                Token::LineTerminator,
+                ident("baz")
+            ],
+            vec![
+                error(source, "trailing '_' in number", 1, 7),
+                error(source, "syntax error, unexpected ']'", 1, 13)
+            ]
+        )
+    );
+}
+
+#[test]
+fn recover_at_end_of_string() {
+    enable_logging();
+    let source = "\"a\\xZZb\\\"\" + \"#{0x}\"\nfoo";
+    assert_eq!(
+        lex_recovering(source),
+        (
+            vec![
+                Token::Error,
+                Token::OpPlus,
+                Token::StringPart { value: "".into() },
+                Token::Error,
+                Token::String { value: "".into() },
+                Token::LineTerminator,
+                ident("foo")
+            ],
+            vec![
+                error(source, "invalid hex escape", 1, 5),
+                error(source, "numeric literal without digits", 1, 19)
+            ]
+        )
+    );
+}
+
+#[test]
+fn recover_at_end_of_input() {
+    enable_logging();
+    let source = "foo \"bar";
+    assert_eq!(
+        lex_recovering(source),
+        (
+            vec![ident("foo"), Token::Error],
+            vec![error(source, "unterminated string meets end of file", 1, 5)]
+        )
+    );
+    let source = "<<EOS\nfoo";
+    assert_eq!(
+        lex_recovering(source),
+        (
+            vec![
+                Token::HeredocStart {
+                    value: "EOS".into()
+                },
+                Token::LineTerminator,
+                Token::Error
+            ],
+            vec![error(
+                source,
+                "can't find string \"EOS\" anywhere before EOF",
+                1,
+                1
+            )]
+        )
+    );
+    let source = "foo \"a#{b";
+    assert_eq!(
+        lex_recovering(source),
+        (
+            vec![
+                ident("foo"),
+                Token::StringPart { value: "a".into() },
+                ident("b"),
+                Token::Error
+            ],
+            vec![error(source, "unterminated string meets end of file", 1, 5)]
+        )
+    );
+}
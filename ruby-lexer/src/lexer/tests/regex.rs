@@ -0,0 +1,109 @@
+use super::*;
+
+fn regex<'a>(value: &'a str, options: &'a str) -> Token<'a> {
+    Token::Regex {
+        value: value.into(),
+        options: options.into(),
+    }
+}
+
+fn ivar(value: &str) -> Token<'_> {
+    Token::InstanceVariable {
+        value: value.into(),
+    }
+}
+
+#[test]
+fn regular_expressions() {
+    enable_logging();
+    assert_eq!(lex_source("//"), Ok(vec![regex("", "")]));
+    assert_eq!(lex_source("/abc/"), Ok(vec![regex("abc", "")]));
+    assert_eq!(lex_source("/a\\/b/i"), Ok(vec![regex("a/b", "i")]));
+    assert_eq!(
+        lex_source("/\\d+\\.\\s\\u{41}/mx"),
+        Ok(vec![regex("\\d+\\.\\s\\u{41}", "mx")])
+    );
+    assert_eq!(lex_source("/a\\\nb/"), Ok(vec![regex("ab", "")]));
+    assert_eq!(
+        lex_source("%r{a}imxouesn"),
+        Ok(vec![regex("a", "imxouesn")])
+    );
+    assert_eq!(
+        lex_source("/a#{@b}c#@d/o"),
+        Ok(vec![
+            Token::StringPart { value: "a".into() },
+            ivar("@b"),
+            Token::StringMid { value: "c".into() },
+            ivar("@d"),
+            regex("", "o")
+        ])
+    );
+}
+
+#[test]
+fn division_operators() {
+    enable_logging();
+    assert_eq!(
+        lex_source("@a / @b"),
+        Ok(vec![
+            ivar("@a"),
+            Token::Whitespace,
+            Token::OpDivide,
+            Token::Whitespace,
+            ivar("@b")
+        ])
+    );
+    assert_eq!(
+        lex_source("@a/2/1"),
+        Ok(vec![
+            ivar("@a"),
+            Token::OpDivide,
+            Token::Integer { value: 2 },
+            Token::OpDivide,
+            Token::Integer { value: 1 }
+        ])
+    );
+    assert_eq!(
+        lex_source("@a /= /b/"),
+        Ok(vec![
+            ivar("@a"),
+            Token::Whitespace,
+            Token::AssignmentOperator { value: "/=".into() },
+            Token::Whitespace,
+            regex("b", "")
+        ])
+    );
+}
+
+#[test]
+fn regex_errors() {
+    enable_logging();
+    assert_eq!(
+        lex_source("/a/k"),
+        Err(LexicalError {
+            message: "unknown regexp option - k".to_owned(),
+            location: location("/a/k", 1, 4)
+        })
+    );
+    assert_eq!(
+        lex_source("/a/ikzm"),
+        Err(LexicalError {
+            message: "unknown regexp options - kz".to_owned(),
+            location: location("/a/ikzm", 1, 4)
+        })
+    );
+    assert_eq!(
+        lex_source("@a = /abc"),
+        Err(LexicalError {
+            message: "unterminated regexp meets end of file".to_owned(),
+            location: location("@a = /abc", 1, 6)
+        })
+    );
+    assert_eq!(
+        lex_source("@a = /a#{b"),
+        Err(LexicalError {
+            message: "unterminated regexp meets end of file".to_owned(),
+            location: location("@a = /a#{b", 1, 6)
+        })
+    );
+}
@@ -0,0 +1,267 @@
+use super::*;
+
+fn string(value: &str) -> Result<Vec<Token<'_>>, LexicalError> {
+    Ok(vec![Token::String {
+        value: value.into(),
+    }])
+}
+
+/// Returns the token of a string literal whose bytes aren't valid UTF-8
+fn string_bytes(bytes: &[u8]) -> Result<Vec<Token<'_>>, LexicalError> {
+    Ok(vec![Token::String {
+        value: StringValue::new(bytes, SourceEncoding::Utf8),
+    }])
+}
+
+#[test]
+fn single_quoted_strings() {
+    enable_logging();
+    assert_eq!(lex_source("''"), string(""));
+    assert_eq!(lex_source("'foo bar'"), string("foo bar"));
+    assert_eq!(lex_source("'multi\nline'"), string("multi\nline"));
+    assert_eq!(
+        lex_source("'a \"quoted\" word'"),
+        string("a \"quoted\" word")
+    );
+
+    // Only backslashes and the delimiter may be escaped
+    assert_eq!(lex_source(r"'it\'s'"), string("it's"));
+    assert_eq!(lex_source(r"'back\\slash'"), string("back\\slash"));
+    assert_eq!(lex_source(r"'\n\t\x41'"), string("\\n\\t\\x41"));
+    assert_eq!(lex_source("'#{foo}'"), string("#{foo}"));
+}
+
+#[test]
+fn double_quoted_strings() {
+    enable_logging();
+    assert_eq!(lex_source("\"\""), string(""));
+    assert_eq!(lex_source("\"foo bar\""), string("foo bar"));
+    assert_eq!(lex_source("\"it's\""), string("it's"));
+    assert_eq!(lex_source("\"héllo wörld\""), string("héllo wörld"));
+    assert_eq!(
+        lex_source("\"one\" 'two'"),
+        Ok(vec![
+            Token::String {
+                value: "one".into()
+            },
+            Token::Whitespace,
+            Token::String {
+                value: "two".into()
+            }
+        ])
+    );
+}
+
+#[test]
+fn escape_sequences() {
+    enable_logging();
+    assert_eq!(
+        lex_source(r#""\n\t\r\f\v\a\e\b\s""#),
+        string("\n\t\r\x0c\x0b\x07\x1b\x08 ")
+    );
+    assert_eq!(lex_source(r#""\\ \" \' \q""#), string("\\ \" ' q"));
+    assert_eq!(
+        lex_source("\"line \\\ncontinued\""),
+        string("line continued")
+    );
+
+    // Octal and hexadecimal
+    assert_eq!(lex_source(r#""\101\0\7\1011""#), string("A\0\x07A1"));
+    assert_eq!(lex_source(r#""\x41\x4a\xA""#), string("AJ\n"));
+    assert_eq!(lex_source(r#""\xE3\x81\x82""#), string("あ"));
+
+    // Unicode
+    assert_eq!(lex_source(r#""\u00e9""#), string("é"));
+    assert_eq!(lex_source(r#""\u00411""#), string("A1"));
+    assert_eq!(lex_source(r#""\u{1F600 41}""#), string("\u{1F600}A"));
+    assert_eq!(lex_source(r#""\u{ 41  42 }""#), string("AB"));
+    assert_eq!(lex_source(r#""\é""#), string("é"));
+
+    // Control and meta characters
+    assert_eq!(
+        lex_source(r#""\C-a\ca\c?\C-?""#),
+        string("\x01\x01\x7f\x7f")
+    );
+    assert_eq!(lex_source(r#""\M-a""#), string_bytes(b"\xe1"));
+    assert_eq!(lex_source(r#""\M-\C-a\C-\M-a""#), string_bytes(b"\x81\x81"));
+
+    // Bytes that don't form a valid character are kept as they are
+    assert_eq!(lex_source(r#""\xff\x82""#), string_bytes(b"\xff\x82"));
+}
+
+#[test]
+fn string_errors() {
+    enable_logging();
+    assert_eq!(
+        lex_source("foo = \"bar"),
+        Err(LexicalError {
+            message: "unterminated string meets end of file".to_owned(),
+            location: location("foo = \"bar", 1, 7)
+        })
+    );
+    assert_eq!(
+        lex_source("'a\nb"),
+        Err(LexicalError {
+            message: "unterminated string meets end of file".to_owned(),
+            location: location("'a\nb", 1, 1)
+        })
+    );
+    assert_eq!(
+        lex_source(r#""\xZZ""#),
+        Err(LexicalError {
+            message: "invalid hex escape".to_owned(),
+            location: location(r#""\xZZ""#, 1, 4)
+        })
+    );
+    assert_eq!(
+        lex_source(r#""\u12""#),
+        Err(LexicalError {
+            message: "invalid Unicode escape".to_owned(),
+            location: location(r#""\u12""#, 1, 4)
+        })
+    );
+    assert_eq!(
+        lex_source(r#""\u{110000}""#),
+        Err(LexicalError {
+            message: "invalid Unicode codepoint (too large)".to_owned(),
+            location: location(r#""\u{110000}""#, 1, 5)
+        })
+    );
+    assert_eq!(
+        lex_source(r#""\u{41"#),
+        Err(LexicalError {
+            message: "unterminated Unicode escape".to_owned(),
+            location: location(r#""\u{41"#, 1, 7)
+        })
+    );
+    assert_eq!(
+        lex_source(r#""\M""#),
+        Err(LexicalError {
+            message: "Invalid escape character syntax".to_owned(),
+            location: location(r#""\M""#, 1, 3)
+        })
+    );
+}
+
+#[test]
+fn interpolated_strings() {
+    enable_logging();
+    assert_eq!(
+        lex_source("\"a#{@b}c\""),
+        Ok(vec![
+            Token::StringPart { value: "a".into() },
+            Token::InstanceVariable { value: "@b".into() },
+            Token::String { value: "c".into() }
+        ])
+    );
+    assert_eq!(
+        lex_source("\"#{1}#{2}\""),
+        Ok(vec![
+            Token::StringPart { value: "".into() },
+            Token::Integer { value: 1 },
+            Token::StringMid { value: "".into() },
+            Token::Integer { value: 2 },
+            Token::String { value: "".into() }
+        ])
+    );
+
+    // Nested strings and braces
+    assert_eq!(
+        lex_source("\"a#{ \"c#{@d}\" }e\""),
+        Ok(vec![
+            Token::StringPart { value: "a".into() },
+            Token::Whitespace,
+            Token::StringPart { value: "c".into() },
+            Token::InstanceVariable { value: "@d".into() },
+            Token::String { value: "".into() },
+            Token::Whitespace,
+            Token::String { value: "e".into() }
+        ])
+    );
+    assert_eq!(
+        lex_source("\"#{{}}\""),
+        Ok(vec![
+            Token::StringPart { value: "".into() },
+            Token::LeftBrace,
+            Token::RightBrace,
+            Token::String { value: "".into() }
+        ])
+    );
+
+    // Interpolation is not performed in single quotes or when escaped
+    assert_eq!(lex_source("'#{@a}'"), string("#{@a}"));
+    assert_eq!(lex_source("\"\\#{@a}\""), string("#{@a}"));
+}
+
+#[test]
+fn interpolated_variables() {
+    enable_logging();
+    assert_eq!(
+        lex_source("\"#@ivar#@@cvar!#$gvar\""),
+        Ok(vec![
+            Token::StringPart { value: "".into() },
+            Token::InstanceVariable {
+                value: "@ivar".into()
+            },
+            Token::StringMid { value: "".into() },
+            Token::ClassVariable {
+                value: "@@cvar".into()
+            },
+            Token::StringMid { value: "!".into() },
+            Token::GlobalVariable {
+                value: "$gvar".into()
+            },
+            Token::String { value: "".into() }
+        ])
+    );
+
+    // A '#' that does not precede a valid variable name is literal
+    assert_eq!(lex_source("\"#@1 #@ #$ # #\""), string("#@1 #@ #$ # #"));
+}
+
+#[test]
+fn interpolation_errors() {
+    enable_logging();
+    assert_eq!(
+        lex_source("\"a#{@b}c"),
+        Err(LexicalError {
+            message: "unterminated string meets end of file".to_owned(),
+            location: location("\"a#{@b}c", 1, 1)
+        })
+    );
+    // The input may end within an interpolation
+    assert_eq!(
+        lex_source("x = \"abc #{x"),
+        Err(LexicalError {
+            message: "unterminated string meets end of file".to_owned(),
+            location: location("x = \"abc #{x", 1, 5)
+        })
+    );
+}
+
+#[test]
+fn unterminated_literals() {
+    enable_logging();
+    let sources = [
+        "'a",
+        "\"a#{b",
+        "\"a#{\"b",
+        "x = <<EOS\nbody\n",
+        "x = <<EOS",
+        "%w(a b",
+        "%q(a",
+        "/a+",
+    ];
+    for source in sources.iter() {
+        // The lexer must end after reporting the literal, so a bounded number of results is taken
+        let results: Vec<_> = Lexer::new(source).take(100).collect();
+        assert!(results.len() < 100, "{}", source);
+        assert_eq!(
+            results.iter().filter(|result| result.is_err()).count(),
+            1,
+            "{}",
+            source
+        );
+        assert!(results.last().unwrap().is_err(), "{}", source);
+    }
+}
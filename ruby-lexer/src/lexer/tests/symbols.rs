@@ -0,0 +1,154 @@
+use super::*;
+
+fn symbol(value: &str) -> Result<Vec<Token<'_>>, LexicalError> {
+    Ok(vec![Token::Symbol {
+        value: value.into(),
+    }])
+}
+
+#[test]
+fn named_symbols() {
+    enable_logging();
+    assert_eq!(lex_source(":sym"), symbol("sym"));
+    assert_eq!(lex_source(":Sym"), symbol("Sym"));
+    assert_eq!(lex_source(":foo?"), symbol("foo?"));
+    assert_eq!(lex_source(":foo!"), symbol("foo!"));
+    assert_eq!(lex_source(":foo="), symbol("foo="));
+    assert_eq!(lex_source(":Foo="), symbol("Foo="));
+    assert_eq!(lex_source(":if"), symbol("if"));
+    assert_eq!(lex_source(":@ivar"), symbol("@ivar"));
+    assert_eq!(lex_source(":@@cvar"), symbol("@@cvar"));
+    assert_eq!(lex_source(":$gvar"), symbol("$gvar"));
+    assert_eq!(
+        lex_source(":foo==@a"),
+        Ok(vec![
+            Token::Symbol {
+                value: "foo".into()
+            },
+            Token::OpDoubleEqual,
+            Token::InstanceVariable { value: "@a".into() }
+        ])
+    );
+}
+
+#[test]
+fn operator_symbols() {
+    enable_logging();
+    for op in &[
+        "+", "-", "*", "/", "%", "**", "+@", "-@", "!", "!=", "!~", "~", "==", "===", "=~", ">",
+        ">=", ">>", "[]", "[]=", "`",
+    ] {
+        assert_eq!(lex_source(&format!(":{}", op)), symbol(op));
+    }
+}
+
+#[test]
+fn quoted_symbols() {
+    enable_logging();
+    assert_eq!(lex_source(":\"foo bar\""), symbol("foo bar"));
+    assert_eq!(lex_source(":'foo #{bar}'"), symbol("foo #{bar}"));
+    assert_eq!(
+        lex_source(":\"dynamic #{@sym}\""),
+        Ok(vec![
+            Token::StringPart {
+                value: "dynamic ".into()
+            },
+            Token::InstanceVariable {
+                value: "@sym".into()
+            },
+            Token::Symbol { value: "".into() }
+        ])
+    );
+    assert_eq!(
+        lex_source(":\"foo"),
+        Err(LexicalError {
+            message: "unterminated string meets end of file".to_owned(),
+            location: location(":\"foo", 1, 1)
+        })
+    );
+    assert_eq!(
+        lex_source(":\"a#{b"),
+        Err(LexicalError {
+            message: "unterminated string meets end of file".to_owned(),
+            location: location(":\"a#{b", 1, 1)
+        })
+    );
+}
+
+#[test]
+fn double_colons() {
+    enable_logging();
+    assert_eq!(
+        lex_source("::Foo"),
+        Ok(vec![
+            Token::DoubleColonPrefix,
+            Token::Constant {
+                value: "Foo".into()
+            }
+        ])
+    );
+    assert_eq!(
+        lex_source("Foo::Bar"),
+        Ok(vec![
+            Token::Constant {
+                value: "Foo".into()
+            },
+            Token::DoubleColon,
+            Token::Constant {
+                value: "Bar".into()
+            }
+        ])
+    );
+    assert_eq!(
+        lex_source("@a::Foo"),
+        Ok(vec![
+            Token::InstanceVariable { value: "@a".into() },
+            Token::DoubleColon,
+            Token::Constant {
+                value: "Foo".into()
+            }
+        ])
+    );
+    assert_eq!(
+        lex_source("@a.b ::"),
+        Ok(vec![
+            Token::InstanceVariable { value: "@a".into() },
+            Token::Dot,
+            Token::Identifier { value: "b".into() },
+            Token::Whitespace,
+            Token::DoubleColonPrefix
+        ])
+    );
+}
+
+#[test]
+fn ternary_else() {
+    enable_logging();
+    assert_eq!(
+        lex_source("@a ? @b : @c"),
+        Ok(vec![
+            Token::InstanceVariable { value: "@a".into() },
+            Token::Whitespace,
+            Token::OpTernaryIf,
+            Token::Whitespace,
+            Token::InstanceVariable { value: "@b".into() },
+            Token::Whitespace,
+            Token::OpTernaryElse,
+            Token::Whitespace,
+            Token::InstanceVariable { value: "@c".into() }
+        ])
+    );
+    assert_eq!(
+        lex_source("@a ? 1 :@c"),
+        Ok(vec![
+            Token::InstanceVariable { value: "@a".into() },
+            Token::Whitespace,
+            Token::OpTernaryIf,
+            Token::Whitespace,
+            Token::Integer { value: 1 },
+            Token::Whitespace,
+            Token::OpTernaryElse,
+            Token::InstanceVariable { value: "@c".into() }
+        ])
+    );
+}
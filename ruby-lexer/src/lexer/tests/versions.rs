@@ -0,0 +1,284 @@
+use super::*;
+
+/// Lexes the source string targeting the given version of Ruby, returning its tokens other than
+/// whitespace or the first lexical error encountered
+fn lex_version(source: &str, version: RubyVersion) -> Result<Vec<Token<'_>>, LexicalError> {
+    Lexer::new(source)
+        .with_ruby_version(version)
+        .filter(|result| !matches!(result, Ok((_, Token::Whitespace, _))))
+        .map(|result| result.map(|(_, token, _)| token))
+        .collect()
+}
+
+fn version_error(source: &str, message: &str, line: usize, col: usize) -> LexicalError {
+    LexicalError {
+        message: message.to_owned(),
+        location: location(source, line, col),
+    }
+}
+
+fn ident(value: &str) -> Token<'_> {
+    Token::Identifier {
+        value: value.into(),
+    }
+}
+
+/// Places the source within a block that declares no parameters, whose opening tokens are
+/// followed by the source's tokens
+fn in_block(source: &str) -> String {
+    format!("x {{ {} }}", source)
+}
+
+#[test]
+fn numbered_parameters() {
+    enable_logging();
+    let source = "_1 + _9 - _10";
+    assert_eq!(
+        lex_version(&in_block(source), RubyVersion::Ruby2_7).unwrap()[2..],
+        vec![
+            Token::NumberedParameter { value: 1 },
+            Token::OpPlus,
+            Token::NumberedParameter { value: 9 },
+            Token::OpMinus,
+            ident("_10"),
+            Token::RightBrace,
+        ]
+    );
+    assert_eq!(
+        lex_version(&in_block(source), RubyVersion::Ruby2_6).unwrap()[2..],
+        vec![
+            ident("_1"),
+            Token::OpPlus,
+            ident("_9"),
+            Token::OpMinus,
+            ident("_10"),
+            Token::RightBrace,
+        ]
+    );
+    assert_eq!(
+        lex_version(&in_block("x._1"), RubyVersion::Ruby3_4).unwrap()[2..],
+        vec![ident("x"), Token::Dot, ident("_1"), Token::RightBrace]
+    );
+
+    // Blocks of any kind may refer to their numbered parameters
+    let blocks = [
+        "x { _1 }",
+        "x do _1 end",
+        "x(1) { _1 }",
+        "-> { _1 }",
+        "-> do _1 end",
+        "x { |a| y { _1 } }",
+        "x do\n  if a then b end\n  _1\nend",
+    ];
+    for source in blocks.iter() {
+        let tokens = lex_version(source, RubyVersion::Ruby3_4).unwrap();
+        assert!(
+            tokens.contains(&Token::NumberedParameter { value: 1 }),
+            "{}",
+            source
+        );
+    }
+
+    // Outside of a block or within a block that declares its parameters, `_1` is an identifier
+    let identifiers = [
+        "_1",
+        "def foo; _1; end",
+        "x { |a| _1 }",
+        "x { || _1 }",
+        "x do |a|\n  _1\nend",
+        "->(a) { _1 }",
+        "-> a do _1 end",
+        "x { y }; _1",
+        "x do y end\n_1",
+        "x do\n  if a then b end\nend\n_1",
+        "def y = x { z }\n_1",
+    ];
+    for source in identifiers.iter() {
+        let tokens = lex_version(source, RubyVersion::Ruby3_4).unwrap();
+        assert!(tokens.contains(&ident("_1")), "{}", source);
+        assert!(
+            !tokens.contains(&Token::NumberedParameter { value: 1 }),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn implicit_parameter() {
+    enable_logging();
+    let parameters = [
+        "it",
+        "it * 2",
+        "it.foo",
+        "it[0]",
+        "it == 1",
+        "it if x",
+        "it ? a : b",
+        "it - 1",
+    ];
+    for source in parameters.iter() {
+        let block = in_block(source);
+        let tokens = lex_version(&block, RubyVersion::Ruby3_4).unwrap();
+        assert_eq!(tokens[2], Token::ImplicitParameter, "{}", source);
+        let tokens = lex_version(&block, RubyVersion::Ruby3_3).unwrap();
+        assert_eq!(tokens[2], ident("it"), "{}", source);
+    }
+    let calls = [
+        "it \"works\" do end",
+        "it { x }",
+        "it(1)",
+        "it = 1",
+        "it -1",
+        "it *a",
+        "it foo",
+    ];
+    for source in calls.iter() {
+        let block = in_block(source);
+        let tokens = lex_version(&block, RubyVersion::Ruby3_4).unwrap();
+        assert_eq!(tokens[2], ident("it"), "{}", source);
+    }
+    assert_eq!(
+        lex_version(&in_block("x.it"), RubyVersion::Ruby3_4).unwrap()[2..],
+        vec![ident("x"), Token::Dot, ident("it"), Token::RightBrace]
+    );
+
+    // Outside of a block, within a block that declares its parameters or once a local variable
+    // named `it` is assigned, `it` is an identifier
+    let identifiers = [
+        "x = it",
+        "it = 1; puts it",
+        "def foo; it; end",
+        "x { |a| it }",
+        "x do |a| it end",
+        "->(a) { it }",
+        "x { it = 1; it }",
+        "x { it ||= 1; it }",
+        "it = 1; x { it }",
+        "x { y }; it",
+    ];
+    for source in identifiers.iter() {
+        let tokens = lex_version(source, RubyVersion::Ruby3_4).unwrap();
+        assert!(!tokens.contains(&Token::ImplicitParameter), "{}", source);
+    }
+    // A local variable assigned within a block is only visible within it
+    let tokens = lex_version("x { it = 1 }; y { it }", RubyVersion::Ruby3_4).unwrap();
+    assert_eq!(tokens.last(), Some(&Token::RightBrace));
+    assert_eq!(tokens[tokens.len() - 2], Token::ImplicitParameter);
+}
+
+#[test]
+fn rightward_assignment() {
+    enable_logging();
+    let source = "x => y";
+    assert_eq!(
+        lex_version(source, RubyVersion::Ruby3_0),
+        Ok(vec![ident("x"), Token::Arrow, ident("y")])
+    );
+    assert_eq!(
+        lex_version(source, RubyVersion::Ruby2_7),
+        Err(version_error(
+            source,
+            "rightward assignment requires Ruby 3.0 or later (targeting Ruby 2.7)",
+            1,
+            3
+        ))
+    );
+
+    // Other arrows are allowed by any version
+    let arrows = [
+        "{a => 1}",
+        "foo a => 1",
+        "foo(a => 1)",
+        "return a => 1",
+        "begin\nrescue E => e\nend",
+        "case x\nin Integer => n\nend",
+    ];
+    for source in arrows.iter() {
+        assert!(
+            lex_version(source, RubyVersion::Ruby2_7).is_ok(),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn endless_method_definitions() {
+    enable_logging();
+    let definitions = [
+        "def x = 1",
+        "def x(a) = a",
+        "def self.x() = 1",
+        "def self.x = 1",
+        "def self::x = 1",
+        "def obj.x(a) = a",
+        "def ==(o) = true",
+    ];
+    for source in definitions.iter() {
+        assert!(
+            lex_version(source, RubyVersion::Ruby3_0).is_ok(),
+            "{}",
+            source
+        );
+        let error = lex_version(source, RubyVersion::Ruby2_7).unwrap_err();
+        assert_eq!(
+            error.message,
+            "endless method definition requires Ruby 3.0 or later (targeting Ruby 2.7)"
+        );
+        assert_eq!(error.location.offset(), source.rfind(" = ").unwrap() + 1);
+    }
+
+    // Other assignments are allowed by any version
+    let assignments = [
+        "def x(a = 1); end",
+        "def x a = 1; end",
+        "def x=(v); end",
+        "(a, b) = 1, 2",
+        "def x\n  y = 1\nend",
+        "def x; self.y = 1; end",
+        "def self.x=(v); end",
+    ];
+    for source in assignments.iter() {
+        assert!(
+            lex_version(source, RubyVersion::Ruby2_6).is_ok(),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn argument_forwarding() {
+    enable_logging();
+    let source = "def x(...)\n  y(1, ... )\nend";
+    assert!(lex_version(source, RubyVersion::Ruby2_7).is_ok());
+    assert_eq!(
+        lex_version(source, RubyVersion::Ruby2_6),
+        Err(version_error(
+            source,
+            "argument forwarding requires Ruby 2.7 or later (targeting Ruby 2.6)",
+            1,
+            7
+        ))
+    );
+    assert!(lex_version("(1...)\n1...2", RubyVersion::Ruby2_6).is_ok());
+}
+
+#[test]
+fn pattern_matching() {
+    enable_logging();
+    let source = "case x\nin [a, *]\nend";
+    assert_eq!(
+        lex_version(source, RubyVersion::Ruby2_6),
+        Err(version_error(
+            source,
+            "pattern matching requires Ruby 2.7 or later (targeting Ruby 2.6)",
+            2,
+            1
+        ))
+    );
+    assert!(lex_version(source, RubyVersion::Ruby2_7).is_ok());
+    assert!(lex_version("x in Integer", RubyVersion::Ruby2_6).is_err());
+    assert!(lex_version("for a in b do\nend", RubyVersion::Ruby2_6).is_ok());
+}
@@ -0,0 +1,87 @@
+use super::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Lexes the source string at the given warning level, returning the warnings reported
+fn lex_warnings(source: &str, level: WarningLevel) -> Vec<Warning> {
+    let mut lexer = Lexer::new(source).with_warning_level(level);
+    for result in lexer.by_ref() {
+        result.unwrap();
+    }
+    lexer.take_warnings()
+}
+
+fn warning(source: &str, kind: WarningKind, message: &str, col: usize) -> Warning {
+    Warning {
+        kind,
+        message: message.to_owned(),
+        location: location(source, 1, col),
+    }
+}
+
+#[test]
+fn verbose_warnings() {
+    enable_logging();
+    let source = "foo *a";
+    assert_eq!(
+        lex_warnings(source, WarningLevel::Verbose),
+        vec![warning(
+            source,
+            WarningKind::ArgumentPrefix,
+            "'*' interpreted as argument prefix",
+            5
+        )]
+    );
+    let source = "foo **a";
+    assert_eq!(
+        lex_warnings(source, WarningLevel::Verbose),
+        vec![warning(
+            source,
+            WarningKind::ArgumentPrefix,
+            "'**' interpreted as argument prefix",
+            5
+        )]
+    );
+    assert_eq!(lex_warnings("foo * a, *b", WarningLevel::Verbose), vec![]);
+    let source = "foo -1";
+    assert_eq!(
+        lex_warnings(source, WarningLevel::Verbose),
+        vec![warning(
+            source,
+            WarningKind::AmbiguousFirstArgument,
+            "ambiguous first argument; put parentheses or a space even after `-' operator",
+            5
+        )]
+    );
+    assert_eq!(lex_warnings(source, WarningLevel::Medium), vec![]);
+}
+
+#[test]
+fn medium_warnings() {
+    enable_logging();
+    let source = "$99999999999";
+    let expected = vec![warning(
+        source,
+        WarningKind::NthRefTooBig,
+        "`$99999999999' is too big for a number variable, always nil",
+        1,
+    )];
+    assert_eq!(lex_warnings(source, WarningLevel::Medium), expected);
+    assert_eq!(lex_warnings(source, WarningLevel::Verbose), expected);
+    assert_eq!(lex_warnings(source, WarningLevel::Silent), vec![]);
+}
+
+#[test]
+fn warning_callback() {
+    enable_logging();
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let sink = received.clone();
+    let mut lexer = Lexer::new("foo &a")
+        .with_warning_level(WarningLevel::Verbose)
+        .with_warning_callback(move |warning| sink.borrow_mut().push(warning.kind));
+    for result in lexer.by_ref() {
+        result.unwrap();
+    }
+    assert_eq!(*received.borrow(), vec![WarningKind::ArgumentPrefix]);
+    assert!(lexer.warnings().is_empty());
+}
@@ -23,17 +23,17 @@ fn continued_lines() {
         tokens,
         Ok(vec![
             Token::Identifier {
-                value: "foo".to_owned()
+                value: "foo".into()
             },
             Token::Whitespace,
             Token::Identifier {
-                value: "bar".to_owned()
+                value: "bar".into()
             },
             Token::Whitespace,
             Token::Identifier {
-                value: "baz".to_owned()
+                value: "baz".into()
             },
-            Token::Newline
+            Token::LineTerminator
         ])
     );
 }
@@ -43,3 +43,38 @@ fn continued_line_only() {
     let tokens = lex_source("\\\n\n");
     assert_eq!(tokens, Ok(vec![Token::Whitespace, Token::Newline]));
 }
+
+#[test]
+fn method_chains() {
+    enable_logging();
+    let tokens = lex_tokens("foo\n  # comment\n\n  .bar\r\n&.baz\n1\n..2\n");
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Identifier {
+                value: "foo".into()
+            },
+            Token::Newline,
+            Token::Comment {
+                value: " comment".into()
+            },
+            Token::Newline,
+            Token::Newline,
+            Token::Dot,
+            Token::Identifier {
+                value: "bar".into()
+            },
+            Token::Newline,
+            Token::SafeNavigation,
+            Token::Identifier {
+                value: "baz".into()
+            },
+            Token::LineTerminator,
+            Token::Integer { value: 1 },
+            Token::LineTerminator,
+            Token::TwoDot,
+            Token::Integer { value: 2 },
+            Token::LineTerminator,
+        ]
+    );
+}
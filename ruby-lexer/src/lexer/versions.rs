@@ -0,0 +1,159 @@
+use super::{LexState, Lexer, LexicalError, Location, RubyVersion, Token};
+
+impl<'src> Lexer<'src> {
+    /// Sets the version of Ruby whose syntax is lexed (the latest version by default)
+    ///
+    /// Syntax introduced after the targeted version, such as an endless method definition when
+    /// targeting Ruby 2.7, is reported as a lexical error. Numbered block parameters (`_1`) and
+    /// the `it` block parameter are only lexed as such from the version introducing them, and as
+    /// identifiers otherwise.
+    pub fn with_ruby_version(mut self, version: RubyVersion) -> Self {
+        self.ruby_version = version;
+        self
+    }
+
+    /// Returns the version of Ruby whose syntax is lexed
+    pub fn ruby_version(&self) -> RubyVersion {
+        self.ruby_version
+    }
+
+    /// Returns an error unless the targeted version of Ruby is at least the version that
+    /// introduced the construct
+    pub(crate) fn require_version(
+        &self,
+        version: RubyVersion,
+        construct: &str,
+        location: Location,
+    ) -> Result<(), LexicalError> {
+        if self.ruby_version >= version {
+            return Ok(());
+        }
+        Err(LexicalError {
+            message: format!(
+                "{} requires Ruby {} or later (targeting Ruby {})",
+                construct, version, self.ruby_version
+            ),
+            location,
+        })
+    }
+
+    /// Tracks the keywords that determine how a later `=` or `=>` in the statement is lexed,
+    /// checking that a pattern matching `in` is supported
+    pub(crate) fn track_keyword(
+        &mut self,
+        keyword: &Token<'static>,
+        location: &Location,
+    ) -> Result<(), LexicalError> {
+        match keyword {
+            Token::KwDef => self.method_definition = Some(self.nesting_level),
+            // An `in` outside of a `for` loop's header begins a pattern
+            Token::KwIn if !self.is_cond_active() => {
+                self.require_version(RubyVersion::Ruby2_7, "pattern matching", location.clone())?;
+                self.arrow_allowed = true;
+            }
+            Token::KwRescue
+            | Token::KwReturn
+            | Token::KwBreak
+            | Token::KwNext
+            | Token::KwYield
+            | Token::KwSuper => self.arrow_allowed = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Checks if a `=` in the given state begins the body of an endless method definition
+    /// (`def x = ...` or `def x(y) = ...`), which follows the method's name or parameters
+    pub(crate) fn is_endless_method_body(&self, state: LexState) -> bool {
+        state == LexState::EXPR_ENDFN && self.method_definition == Some(self.nesting_level)
+    }
+
+    /// Returns the state following a `.` or `::` lexed in the given state, which precedes a
+    /// method's name rather than a method call when it follows the receiver of a singleton method
+    /// definition (`def self.x`)
+    pub(crate) fn state_after_dot(&self, state: LexState) -> LexState {
+        if state == LexState::EXPR_ENDFN && self.method_definition == Some(self.nesting_level) {
+            LexState::EXPR_FNAME
+        } else {
+            LexState::EXPR_DOT
+        }
+    }
+
+    /// Checks if a `=>` is a rightward assignment rather than separating a hash's key from its
+    /// value or introducing the name bound by a rescue clause or pattern
+    pub(crate) fn is_rightward_assignment(&self) -> bool {
+        self.nesting_level == 0
+            && self.lex_strterm.is_empty()
+            && !self.arrow_allowed
+            && !self.is_cmdarg_active()
+    }
+
+    /// Checks if the upcoming `...` forwards a method's arguments (`(...)` or `(a, ...)`), given
+    /// whether it begins an expression
+    pub(crate) fn is_argument_forwarding(&self, beg: bool) -> bool {
+        let mut i = 3;
+        while self.char(i).is_some_and(Self::is_space) {
+            i += 1;
+        }
+        beg && self.nesting_level > 0 && self.char(i) == Some(')')
+    }
+
+    /// Checks if the identifier is a numbered parameter (`_1` to `_9`) of a block that declares
+    /// no parameters
+    pub(crate) fn is_numbered_parameter(&self, ident: &str) -> bool {
+        let bytes = ident.as_bytes();
+        self.ruby_version >= RubyVersion::Ruby2_7
+            && self.is_in_parameterless_block()
+            && bytes.len() == 2
+            && bytes[0] == b'_'
+            && (b'1'..=b'9').contains(&bytes[1])
+    }
+
+    /// Checks if the `it` just lexed refers to the implicit parameter of a block that declares no
+    /// parameters, which it does unless it is a local variable or followed by arguments, a block
+    /// or an assignment
+    pub(crate) fn is_implicit_parameter(&self) -> bool {
+        if self.ruby_version < RubyVersion::Ruby3_4
+            || !self.is_in_parameterless_block()
+            || self.is_it_assigned()
+        {
+            return false;
+        }
+        let mut i = 0;
+        while self.char(i).is_some_and(Self::is_whitespace) {
+            i += 1;
+        }
+        let spaced = i > 0;
+        match self.char(i) {
+            None => true,
+            Some('(') | Some('{') => false,
+            Some('=') => matches!(self.char(i + 1), Some('=') | Some('~') | Some('>')),
+            // A modifier keyword follows the parameter, while other words are arguments
+            Some(c) if Self::is_identchar(c) => {
+                let rest = &self.source[self.location.offset + i..];
+                let len = rest.find(|c| !Self::is_identchar(c)).unwrap_or(rest.len());
+                matches!(
+                    &rest[..len],
+                    "if" | "unless"
+                        | "while"
+                        | "until"
+                        | "rescue"
+                        | "and"
+                        | "or"
+                        | "then"
+                        | "in"
+                        | "end"
+                )
+            }
+            Some('"') | Some('\'') | Some('`') | Some('@') | Some('$') => false,
+            // An operator is binary unless it is separated from its operand only on the right
+            Some(c) if !spaced || !"-+*&:[?/%<!~".contains(c) => true,
+            Some(c) => match self.char(i + 1) {
+                None | Some('=') => true,
+                Some('.') => c == '&',
+                Some('<') if c == '<' => self.char(i + 2).is_none_or(Self::is_space),
+                Some(next) => Self::is_space(next) || (next == c && c != ':'),
+            },
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use super::{Lexer, Location, Warning, WarningKind, WarningLevel};
+
+impl<'src> Lexer<'src> {
+    /// Sets the verbosity of the warnings reported while lexing (`WarningLevel::Medium` by
+    /// default)
+    pub fn with_warning_level(mut self, level: WarningLevel) -> Self {
+        self.warning_level = level;
+        self
+    }
+
+    /// Passes each warning reported while lexing to the given callback, rather than collecting
+    /// it
+    pub fn with_warning_callback<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(Warning) + 'static,
+    {
+        self.warning_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns the warnings reported while lexing
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Removes and returns the warnings reported while lexing
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Reports a warning at the lexer's current location
+    pub(crate) fn warn(&mut self, kind: WarningKind, message: &str) {
+        let location = self.get_pos();
+        self.warn_at(kind, message, location);
+    }
+
+    /// Reports a warning at the given location, if the warning level allows it
+    pub(crate) fn warn_at(&mut self, kind: WarningKind, message: &str, location: Location) {
+        if self.warning_level < kind.level() {
+            return;
+        }
+        let warning = Warning {
+            kind,
+            message: message.to_owned(),
+            location,
+        };
+        match self.warning_callback.as_mut() {
+            Some(callback) => callback(warning),
+            None => self.warnings.push(warning),
+        }
+    }
+}
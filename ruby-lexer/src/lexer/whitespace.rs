@@ -1,9 +1,6 @@
 use super::{LexResult, Lexer, Token};
 
-impl<T> Lexer<T>
-where
-    T: Iterator<Item = char>,
-{
+impl<'src> Lexer<'src> {
     /// Helper function to determine if a character is whitespace (not including newline)
     pub(crate) fn is_whitespace(c: char) -> bool {
         match c {
@@ -12,24 +9,48 @@ where
         }
     }
 
+    /// Helper function to determine if a character is whitespace (including newline)
+    pub(crate) fn is_space(c: char) -> bool {
+        c == '\n' || Self::is_whitespace(c)
+    }
+
+    /// Checks if the line following the upcoming newline continues a method chain by starting
+    /// with `.` or `&.` (but not `..`), skipping any blank or comment-only lines
+    pub(crate) fn is_method_chain_continued(&self) -> bool {
+        let rest = self.source[self.location.offset..].as_bytes();
+        let mut i = 0;
+        while i < rest.len() {
+            match rest[i] {
+                b' ' | b'\t' | b'\x0b' | b'\x0c' | b'\r' | b'\n' => i += 1,
+                b'#' => {
+                    i += rest[i..]
+                        .iter()
+                        .position(|b| *b == b'\n')
+                        .unwrap_or(rest.len() - i)
+                }
+                b'.' => return rest.get(i + 1) != Some(&b'.'),
+                b'&' => return rest.get(i + 1) == Some(&b'.'),
+                _ => return false,
+            }
+        }
+        false
+    }
+
     /// Lexes a sequence of whitespace characters and escaped newlines
-    pub(crate) fn lex_whitespace(&mut self) -> LexResult {
+    pub(crate) fn lex_whitespace(&mut self) -> LexResult<'src> {
         let tok_start = self.get_pos();
         loop {
-            if let Some(c) = self.char(0) {
-                if Self::is_whitespace(c) {
-                    // Handle a normal whitespace
-                    self.next_char();
-                    continue;
-                } else if c == '\\' && self.char(1) == Some('\n') {
-                    // Handle line continuations
-                    self.next_char();
-                    self.next_char();
-                    continue;
-                }
+            self.skip_while(Self::is_whitespace);
+            // Handle line continuations
+            if self.char(0) == Some('\\') && self.char(1) == Some('\n') {
+                self.next_char();
+                self.next_char();
+                continue;
             }
             break;
         }
+        // Whitespace does not affect whether the upcoming token may start a command
+        self.command_state = self.prev_command_state;
         Ok((tok_start, Token::Whitespace, self.get_pos()))
     }
 }
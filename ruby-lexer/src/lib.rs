@@ -1,7 +1,8 @@
 //! This crate provides a library for lexing Ruby source code.
 //!
 //! The lexer is an adaptation of [mruby's parse.y](https://github.com/mruby/mruby/blob/2.0.1/mrbgems/mruby-compiler/core/parse.y),
-//! which is compatible with Ruby's 2.x syntax.
+//! which is compatible with Ruby's 2.x syntax, extended with the syntax of later versions up to
+//! Ruby 3.4. The targeted version is set with `Lexer::with_ruby_version`.
 //!
 //! # Usage
 //! ...
@@ -21,19 +22,31 @@
 #[macro_use]
 extern crate bitflags;
 
+mod encoding;
 mod error;
 mod lexer;
 mod location;
+mod magic_comments;
 pub mod plugins;
+mod string_value;
 mod tokens;
+mod trivia;
+mod version;
+mod warning;
 
+pub use encoding::SourceEncoding;
 pub use error::LexicalError;
+pub use lexer::{Checkpoint, IncrementalLexer, Lexer, LosslessLexer, TokenDelta};
 pub use location::Location;
+pub use magic_comments::{MagicComments, ShareableConstantValue};
+pub use string_value::StringValue;
 pub use tokens::Token;
-pub use lexer::Lexer;
+pub use trivia::{LosslessToken, Trivia, TriviaKind};
+pub use version::RubyVersion;
+pub use warning::{Warning, WarningKind, WarningLevel};
 
 /// Composite type that tracks a token and its starting and ending location
-pub type SpannedToken = (Location, Token, Location);
+pub type SpannedToken<'src> = (Location, Token<'src>, Location);
 
 /// Type used to track the success of a lexing operation
-pub type LexResult = Result<SpannedToken, LexicalError>;
+pub type LexResult<'src> = Result<SpannedToken<'src>, LexicalError>;
@@ -4,10 +4,23 @@
 use std::fmt;
 
 /// Struct used to track the line and column numbers of lexed tokens
+///
+/// Lines and columns are 1-based, while offsets are 0-based. The end location of a token is
+/// exclusive: it is the location of the first character following the token, so a token's
+/// source text is `&source[start.offset()..end.offset()]`.
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Location {
     pub line: usize,
+    /// The column, counted in chars
     pub col: usize,
+    /// The column, counted in UTF-8 bytes
+    pub byte_col: usize,
+    /// The column, counted in UTF-16 code units
+    pub utf16_col: usize,
+    /// The offset from the start of the input, counted in UTF-8 bytes
+    pub offset: usize,
+    /// The offset from the start of the input, counted in chars
+    pub char_offset: usize,
 }
 
 impl fmt::Display for Location {
@@ -17,8 +30,41 @@ impl fmt::Display for Location {
 }
 
 impl Location {
+    /// Creates a location from a line and a char column, with byte and UTF-16 columns equal to
+    /// the char column and no offsets
     pub fn new(line: usize, col: usize) -> Self {
-        Location { line, col }
+        Location {
+            line,
+            col,
+            byte_col: col,
+            utf16_col: col,
+            offset: 0,
+            char_offset: 0,
+        }
+    }
+    /// Returns the location of the given line and char column within the source
+    ///
+    /// As in the lexer, a `\r\n` line ending counts as a single character. Returns `None` if the
+    /// source doesn't contain the position.
+    pub fn from_source(source: &str, line: usize, col: usize) -> Option<Self> {
+        let mut location = Location::default();
+        location.reset();
+        let mut chars = source.chars();
+        while location.line != line || location.col != col {
+            if location.line > line {
+                return None;
+            }
+            match chars.next()? {
+                '\r' if chars.as_str().starts_with('\n') => {
+                    chars.next();
+                    location.offset += 1;
+                    location.char_offset += 1;
+                    location.advance('\n');
+                }
+                c => location.advance(c),
+            }
+        }
+        Some(location)
     }
     pub fn line(&self) -> usize {
         self.line
@@ -26,15 +72,40 @@ impl Location {
     pub fn col(&self) -> usize {
         self.col
     }
+    pub fn byte_col(&self) -> usize {
+        self.byte_col
+    }
+    pub fn utf16_col(&self) -> usize {
+        self.utf16_col
+    }
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+    pub fn char_offset(&self) -> usize {
+        self.char_offset
+    }
     pub fn reset(&mut self) {
         self.line = 1;
         self.col = 1;
+        self.byte_col = 1;
+        self.utf16_col = 1;
+        self.offset = 0;
+        self.char_offset = 0;
     }
-    pub fn move_right(&mut self) {
-        self.col += 1;
-    }
-    pub fn newline(&mut self) {
-        self.line += 1;
-        self.col = 1;
+    /// Moves the location past the given character
+    #[inline]
+    pub fn advance(&mut self, c: char) {
+        self.offset += c.len_utf8();
+        self.char_offset += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+            self.byte_col = 1;
+            self.utf16_col = 1;
+        } else {
+            self.col += 1;
+            self.byte_col += c.len_utf8();
+            self.utf16_col += c.len_utf16();
+        }
     }
 }
@@ -0,0 +1,96 @@
+// This module defines the values that may be set by magic comments, along with helpers used by
+// the lexer to read them from the contents of comments.
+
+/// The values set by the magic comments of a source file
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MagicComments {
+    /// The source encoding, set by an `encoding` or `coding` comment on the first line (or the
+    /// second line, after a shebang)
+    pub encoding: Option<String>,
+    /// Set by a `frozen_string_literal` comment preceding the first token
+    pub frozen_string_literal: Option<bool>,
+    /// The latest value set by a `warn_indent` comment
+    pub warn_indent: Option<bool>,
+    /// The latest value set by a `shareable_constant_value` comment, which applies to the
+    /// code following it
+    pub shareable_constant_value: Option<ShareableConstantValue>,
+}
+
+/// The values of the `shareable_constant_value` magic comment
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShareableConstantValue {
+    None,
+    Literal,
+    ExperimentalEverything,
+    ExperimentalCopy,
+}
+
+impl ShareableConstantValue {
+    /// Returns the value named by a magic comment, if it is valid
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" => Some(ShareableConstantValue::None),
+            "literal" => Some(ShareableConstantValue::Literal),
+            "experimental_everything" => Some(ShareableConstantValue::ExperimentalEverything),
+            "experimental_copy" => Some(ShareableConstantValue::ExperimentalCopy),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the `name: value` pairs of a magic comment, given the comment's text without its `#`
+///
+/// A magic comment either consists of a single pair, or holds pairs separated by semicolons
+/// between two `-*-` markers (Emacs style). Names are lowercased, with dashes replaced by
+/// underscores.
+pub(crate) fn parse_magic_comment(comment: &str) -> Vec<(String, String)> {
+    if let Some(start) = comment.find("-*-") {
+        let rest = &comment[start + 3..];
+        return match rest.find("-*-") {
+            Some(end) => rest[..end].split(';').filter_map(parse_pair).collect(),
+            None => Vec::new(),
+        };
+    }
+    parse_pair(comment).into_iter().collect()
+}
+
+/// Parses a single `name: value` pair of a magic comment
+fn parse_pair(text: &str) -> Option<(String, String)> {
+    let colon = text.find(':')?;
+    let name = text[..colon].trim();
+    let value = text[colon + 1..].trim();
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() || !name.chars().all(is_name_char) {
+        return None;
+    }
+    let value = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    };
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '"') {
+        return None;
+    }
+    let name = name.to_ascii_lowercase().replace('-', "_");
+    Some((name, value.to_owned()))
+}
+
+/// Returns the encoding named by a `coding:` or `coding=` within a comment that isn't a magic
+/// comment (e.g. `# vim: set fileencoding=utf-8 :`)
+pub(crate) fn parse_coding(comment: &str) -> Option<String> {
+    let mut rest = comment;
+    while let Some(index) = rest.find("coding") {
+        rest = &rest[index + 6..];
+        if rest.starts_with(':') || rest.starts_with('=') {
+            let value: String = rest[1..]
+                .trim_start()
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+                .collect();
+            if !value.is_empty() {
+                return Some(value);
+            }
+        }
+    }
+    None
+}
@@ -3,9 +3,8 @@
 /// This iterator standardizes the different line endings used by various platforms. Most notably,
 /// Unix-like systems utilize "`\n`" as their line ending, and Windows sysems utilize "`\r\n`".
 ///
-/// The lexer provided by this crate only recognizes the "`\n`" Unix-style line ending, so it is
-/// recommended that input streams are first wrapped by this iterator before being passed to the
-/// lexer.
+/// The lexer provided by this crate recognizes both line endings itself, so this iterator is only
+/// needed by consumers of other character streams.
 ///
 /// # Example
 /// ```
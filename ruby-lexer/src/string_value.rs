@@ -0,0 +1,113 @@
+// This module defines the value of a string literal token, which holds the literal's bytes in the
+// encoding of the source file rather than as decoded text.
+
+use crate::encoding::SourceEncoding;
+use std::borrow::Cow;
+use std::fmt;
+
+/// The contents of a string literal, as bytes in the encoding of the source file
+///
+/// Escape sequences (e.g. `"\xff"`) may produce bytes that don't form a valid character in the
+/// encoding, so the bytes are kept as they are and only decoded on request. The bytes are borrowed
+/// from the source when the literal holds no escapes or line continuations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StringValue<'src> {
+    bytes: Cow<'src, [u8]>,
+    encoding: SourceEncoding,
+}
+
+impl<'src> StringValue<'src> {
+    /// Creates a value from bytes in the given encoding
+    pub fn new<B: Into<Cow<'src, [u8]>>>(bytes: B, encoding: SourceEncoding) -> Self {
+        StringValue {
+            bytes: bytes.into(),
+            encoding,
+        }
+    }
+
+    /// Returns the bytes of the value in its encoding
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the encoding of the value's bytes
+    pub fn encoding(&self) -> SourceEncoding {
+        self.encoding
+    }
+
+    /// Checks whether the value was borrowed from the source
+    pub fn is_borrowed(&self) -> bool {
+        match self.bytes {
+            Cow::Borrowed(_) => true,
+            Cow::Owned(_) => false,
+        }
+    }
+
+    /// Decodes the value, returning `None` if its bytes are not valid in its encoding
+    pub fn to_str(&self) -> Option<Cow<'_, str>> {
+        match self.encoding {
+            SourceEncoding::Utf8 => std::str::from_utf8(&self.bytes).ok().map(Cow::Borrowed),
+            encoding => encoding.decode(&self.bytes).ok().map(Cow::Owned),
+        }
+    }
+
+    /// Decodes the value, replacing malformed sequences with `U+FFFD REPLACEMENT CHARACTER`
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        match self.encoding {
+            SourceEncoding::Utf8 => String::from_utf8_lossy(&self.bytes),
+            encoding => encoding.decode_lossy(&self.bytes).into(),
+        }
+    }
+
+    /// Decodes the value as `to_string_lossy` does, borrowing from the source where possible
+    pub(crate) fn into_string_lossy(self) -> Cow<'src, str> {
+        match (self.encoding, self.bytes) {
+            (SourceEncoding::Utf8, Cow::Borrowed(bytes)) => String::from_utf8_lossy(bytes),
+            (SourceEncoding::Utf8, Cow::Owned(bytes)) => match String::from_utf8(bytes) {
+                Ok(s) => s.into(),
+                Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned().into(),
+            },
+            (encoding, bytes) => encoding.decode_lossy(&bytes).into(),
+        }
+    }
+
+    /// Returns the bytes of the value for modification, copying them if borrowed
+    pub(crate) fn bytes_mut(&mut self) -> &mut Vec<u8> {
+        self.bytes.to_mut()
+    }
+
+    /// Converts the value into one that owns its bytes
+    pub fn into_owned(self) -> StringValue<'static> {
+        StringValue {
+            bytes: Cow::Owned(self.bytes.into_owned()),
+            encoding: self.encoding,
+        }
+    }
+}
+
+impl<'src> From<&'src str> for StringValue<'src> {
+    fn from(s: &'src str) -> Self {
+        StringValue::new(s.as_bytes(), SourceEncoding::Utf8)
+    }
+}
+
+impl<'src> From<String> for StringValue<'src> {
+    fn from(s: String) -> Self {
+        StringValue::new(s.into_bytes(), SourceEncoding::Utf8)
+    }
+}
+
+impl<'src> From<Cow<'src, str>> for StringValue<'src> {
+    fn from(s: Cow<'src, str>) -> Self {
+        match s {
+            Cow::Borrowed(s) => s.into(),
+            Cow::Owned(s) => s.into(),
+        }
+    }
+}
+
+impl<'src> fmt::Display for StringValue<'src> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_string_lossy())
+    }
+}
@@ -1,8 +1,14 @@
-//use num_bigint::BigInt;
+use crate::StringValue;
+use num_bigint::BigInt;
+use std::borrow::Cow;
 
 /// Ruby source can be tokenized into a sequence of these tokens
+///
+/// Values that appear verbatim in the source (such as identifiers, comments and string literals
+/// without escapes) borrow from the source being lexed, while other values are owned.
+/// The values of string literals hold their bytes in the source encoding (see `StringValue`).
 #[derive(Clone, Debug, PartialEq)]
-pub enum Token {
+pub enum Token<'src> {
     // 8.7.2 - Keywords (alphanumerically)
     KwLINE,     // __LINE__
     KwENCODING, // __ENCODING__
@@ -56,6 +62,11 @@ pub enum Token {
     // Other tokens (for now)
     // 8.1 - Input elements
     EndOfFile,
+    /// Covers malformed input that was skipped by a lexer recovering from errors
+    ///
+    /// The lexical error describing the input is recorded by the lexer (see
+    /// `Lexer::with_error_recovery`).
+    Error,
     // 8.3 - Line terminators
     Separator,      // ;
     Newline,        // \n or \r\n, syntatically insignificant
@@ -64,42 +75,58 @@ pub enum Token {
     Whitespace, // tab (0x09), vertical tab (0x0b), form feed (0x0c), carriage return (0x0d), space (0x20)
     // 8.5 - Comments
     Comment {
-        value: String,
+        value: Cow<'src, str>,
+    },
+    /// **Characters:** `=begin` ... `=end`
+    ///
+    /// A multi-line comment (embedded document) starting and ending at the beginning of a line.
+    EmbeddedDocument {
+        value: Cow<'src, str>,
     },
     // 8.6 - End of program markers
     EndOfProgramMarker, // __END__
     // 8.7.3 - Identifiers
     /// ### Original Grammar: `tIDENTIFIER`
     Identifier {
-        value: String,
+        value: Cow<'src, str>,
     },
     /// ### Original Grammar: `tGVAR`
     GlobalVariable {
-        value: String,
+        value: Cow<'src, str>,
     },
     /// ### Original Grammar: `tCVAR`
     ClassVariable {
-        value: String,
+        value: Cow<'src, str>,
     },
     /// ### Original Grammar: `tIVAR`
     InstanceVariable {
-        value: String,
+        value: Cow<'src, str>,
     },
     /// ### Original Grammar: `tCONSTANT`
     Constant {
-        value: String,
+        value: Cow<'src, str>,
     },
     /// ### Original Grammar: `tFID`
     FunctionIdentifier {
-        value: String,
+        value: Cow<'src, str>,
     },
     /// ### Original Grammar: `tLABEL_TAG`
+    ///
+    /// Represents the key of a hash element or keyword argument written as a label (`key:`),
+    /// along with its colon. A quoted label (`"key":`) is lexed as a string literal whose
+    /// closing segment is emitted as a label.
     LabelTag {
-        value: String,
+        value: Cow<'src, str>,
     },
     AssignmentLikeMethodIdentifier {
-        value: String,
+        value: Cow<'src, str>,
+    },
+    /// A numbered block parameter (`_1` to `_9`), lexed when targeting Ruby 2.7 or later
+    NumberedParameter {
+        value: u8,
     },
+    /// The `it` block parameter, lexed when targeting Ruby 3.4 or later
+    ImplicitParameter,
     // 8.7.4 - Punctuators
     /// ### Original Grammar: `tLBRACK`
     /// **Characters:** `[`
@@ -122,6 +149,11 @@ pub enum Token {
     /// ### Original Grammar: `tLBRACE_ARG`
     /// **Characters:** `{`
     LeftBraceArg,
+    /// ### Original Grammar: `'{'`
+    /// **Characters:** `{`
+    ///
+    /// This token begins a block attached to a method call (e.g. `foo { |x| x }`).
+    LeftBraceBlock,
     /// ### Original Grammar: _None_
     /// **Characters:** `}`
     RightBrace,
@@ -144,6 +176,14 @@ pub enum Token {
     OpTernaryIf,   // ?
     OpTernaryElse, // :
     Arrow,         // => tASSOC
+    /// ### Original Grammar: `tLAMBDA`
+    /// **Characters:** `->`
+    Lambda,
+    /// ### Original Grammar: `tLAMBEG`
+    /// **Characters:** `{`
+    ///
+    /// This token begins the body of a lambda literal (e.g. `->(x) { x }`).
+    LambdaBegin,
     // 8.7.5 - Operators
     OpNot,      // !
     /// ### Original Grammar: `tNEQ`
@@ -216,13 +256,19 @@ pub enum Token {
     /// ### Original Grammar: `tOP_ASGN`
     /// This token represents an assignment operator
     AssignmentOperator {
-        value: String,
+        value: Cow<'src, str>,
     },
     // 8.7.6 - Literals
     /// ### Original Grammar: `tINTEGER`
     Integer {
         value: isize,
     },
+    /// ### Original Grammar: `tINTEGER`
+    ///
+    /// Represents an integer literal whose value does not fit within an `isize`.
+    BigInteger {
+        value: BigInt,
+    },
     /// ### Original Grammar: `tFLOAT`
     Float {
         value: f64,
@@ -232,7 +278,7 @@ pub enum Token {
     /// Represents the `?<char>` character literal notation that can be used to build single character strings.
     /// Valid forms include:
     /// * `?x` - where `x` represents any unescaped ASCII character or any valid character escape sequence
-    /// * `?cx` - Control + `x`
+    /// * `?\cx` - Control + `x`
     /// * `?\C-x` - Control + `x`
     /// * `?\M-x` - Meta + `x`
     /// * `?\C-\M-x` - Control + Meta + `x`
@@ -240,50 +286,89 @@ pub enum Token {
     ///
     /// See [Ruby's string literal syntax](https://github.com/ruby/ruby/blob/trunk/doc/syntax/literals.rdoc#strings) for more info.
     Char {
-        value: String,
+        value: StringValue<'src>,
     },
-    Complex {
-        real: f64,
-        imag: f64,
+    /// ### Original Grammar: `tRATIONAL`
+    ///
+    /// Represents a rational literal (e.g. `3r` or `1.5r`) as an exact fraction in lowest terms.
+    Rational {
+        numerator: BigInt,
+        denominator: BigInt,
+    },
+    /// ### Original Grammar: `tIMAGINARY`
+    ///
+    /// Represents an imaginary literal (e.g. `2i`, `1.5i` or `3ri`), holding the numeric token of
+    /// the literal's imaginary part.
+    Imaginary {
+        value: Box<Token<'src>>,
     },
     /// ### Original Grammar: `tXSTRING`
     ///
     /// Represents a backtick string (which captures the result of a subshell). Backtick strings come in two forms:
     /// * `` `backtick string` ``
-    /// * `%{backtick string}`
+    /// * `%x{backtick string}`
     XString {
-        value: String,
+        value: StringValue<'src>,
     },
     /// ### Original Grammar: `tSTRING`
     String {
-        value: String,
+        value: StringValue<'src>,
     },
     /// ### Original Grammar: `tSTRING_PART`
     StringPart {
-        value: String,
+        value: StringValue<'src>,
     },
     /// ### Original Grammar: `tSTRING_MID`
     StringMid {
-        value: String,
+        value: StringValue<'src>,
+    },
+    /// ### Original Grammar: `tWORDS_BEG`
+    ///
+    /// Represents the opening delimiter of a `%w` or `%W` word list, which is followed by a string
+    /// literal for each of the list's words and a `ListEnd` token.
+    WordsStart,
+    /// ### Original Grammar: `tSYMBOLS_BEG`
+    ///
+    /// Represents the opening delimiter of a `%i` or `%I` symbol list, which is followed by a
+    /// symbol literal for each of the list's symbols and a `ListEnd` token.
+    SymbolsStart,
+    /// ### Original Grammar: _None_
+    ///
+    /// Represents the closing delimiter of a `%w`, `%W`, `%i` or `%I` list.
+    ListEnd,
+    /// ### Original Grammar: `tHEREDOC_BEG`
+    ///
+    /// Represents the start of a heredoc (e.g. `<<EOS`, `<<-EOS`, `<<~EOS` or `<<'EOS'`), where
+    /// the value is the heredoc's terminating identifier. The heredoc's body is emitted as a string
+    /// literal once the rest of the line has been lexed.
+    HeredocStart {
+        value: Cow<'src, str>,
     },
     /// ### Original Grammar: `tREGEXP`
+    ///
+    /// Represents a regular expression literal (`/regex/` or `%r{regex}`), where `options`
+    /// contains any of the trailing option flags (`i`, `m`, `x`, `o`, `u`, `e`, `s` and `n`).
     Regex {
-        value: String,
+        value: StringValue<'src>,
+        options: Cow<'src, str>,
     },
     /// ### Original Grammar: `tNTH_REF`
     RegexNthRef {
-        value: String,
+        value: Cow<'src, str>,
     },
     /// ### Original Grammar: `tBACK_REF`
     RegexBackRef {
-        value: String,
+        value: Cow<'src, str>,
     },
     Symbol {
-        value: String,
+        value: Cow<'src, str>,
     },
     // Character tokens
     At,        // @
     Dot,       // .
+    /// ### Original Grammar: `tANDDOT`
+    /// **Characters:** `&.`
+    SafeNavigation,
     Star,      // * tSTAR
     /// ### Original Grammar: `tDSTAR`
     /// **Characters:** `**`
@@ -291,5 +376,16 @@ pub enum Token {
     /// Represents `**` when used as an argument prefix. This is differentiated from the `tPOW` token,
     /// which is used as the exponentiation operator.
     TwoStar,
+    /// ### Original Grammar: `tAMPER`
+    /// **Characters:** `&`
+    ///
+    /// Represents `&` when used as a block argument prefix. This is differentiated from the
+    /// bitwise and operator.
+    Ampersand,
     Backslash, // \
+    /// ### Original Grammar: `` '`' ``
+    /// **Characters:** `` ` ``
+    ///
+    /// Represents the backtick when used as a method name (e.g. `` def `(cmd) ``).
+    Backtick,
 }
@@ -0,0 +1,59 @@
+// This module defines the tokens produced by the lexer's lossless mode, which carry their exact
+// source text along with the trivia surrounding them.
+
+use crate::{Location, Token};
+use std::borrow::Cow;
+use std::fmt;
+
+/// The kinds of trivia: source text between tokens that has no syntactic meaning
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// Spaces, tabs, vertical tabs, form feeds and carriage returns
+    Whitespace,
+    /// A backslash followed by a line ending, which continues the line
+    LineContinuation,
+    /// A line ending (`\n` or `\r\n`) that doesn't terminate a statement
+    Newline,
+    /// A single-line comment, including its `#`
+    Comment,
+    /// An embedded document (`=begin` ... `=end`), including the rest of its closing line
+    EmbeddedDocument,
+}
+
+/// A piece of trivia along with its exact source text
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trivia<'src> {
+    pub kind: TriviaKind,
+    pub text: Cow<'src, str>,
+}
+
+/// A token produced by the lexer's lossless mode (see `Lexer::into_lossless`)
+///
+/// Trailing trivia holds the whitespace and comments that follow the token on its line, while
+/// leading trivia holds any other trivia preceding the token. Concatenating the leading trivia,
+/// text and trailing trivia of every token (as done by the `Display` implementation) reproduces
+/// the source.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LosslessToken<'src> {
+    pub token: Token<'src>,
+    pub start: Location,
+    pub end: Location,
+    /// The token's exact source text
+    pub text: Cow<'src, str>,
+    pub leading_trivia: Vec<Trivia<'src>>,
+    pub trailing_trivia: Vec<Trivia<'src>>,
+}
+
+impl<'src> fmt::Display for LosslessToken<'src> {
+    /// Writes the token's source text, including its trivia
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for trivia in &self.leading_trivia {
+            f.write_str(&trivia.text)?;
+        }
+        f.write_str(&self.text)?;
+        for trivia in &self.trailing_trivia {
+            f.write_str(&trivia.text)?;
+        }
+        Ok(())
+    }
+}
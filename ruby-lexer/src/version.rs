@@ -0,0 +1,50 @@
+// This module defines the versions of Ruby whose syntax the lexer can target.
+
+use std::fmt;
+
+/// A version of Ruby whose syntax is lexed (see `Lexer::with_ruby_version`)
+///
+/// Versions are ordered from oldest to newest. Each version accepts the syntax introduced by the
+/// versions preceding it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RubyVersion {
+    /// Ruby 2.6, along with the earlier 2.x versions
+    Ruby2_6,
+    /// Ruby 2.7, which introduced numbered block parameters (`_1`), argument forwarding (`...`)
+    /// and pattern matching (`case`/`in`)
+    Ruby2_7,
+    /// Ruby 3.0, which introduced rightward assignment (`expr => pattern`) and endless method
+    /// definitions (`def x = ...`)
+    Ruby3_0,
+    /// Ruby 3.1, whose new syntax (e.g. the anonymous block argument `&`) is not gated by the lexer
+    Ruby3_1,
+    /// Ruby 3.2, whose new syntax (e.g. anonymous rest arguments `*` and `**`) is not gated by the
+    /// lexer
+    Ruby3_2,
+    /// Ruby 3.3, which introduced no syntax gated by the lexer
+    Ruby3_3,
+    /// Ruby 3.4, which introduced the `it` block parameter
+    Ruby3_4,
+}
+
+impl Default for RubyVersion {
+    /// Returns the latest version
+    fn default() -> Self {
+        RubyVersion::Ruby3_4
+    }
+}
+
+impl fmt::Display for RubyVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let version = match self {
+            RubyVersion::Ruby2_6 => "2.6",
+            RubyVersion::Ruby2_7 => "2.7",
+            RubyVersion::Ruby3_0 => "3.0",
+            RubyVersion::Ruby3_1 => "3.1",
+            RubyVersion::Ruby3_2 => "3.2",
+            RubyVersion::Ruby3_3 => "3.3",
+            RubyVersion::Ruby3_4 => "3.4",
+        };
+        f.write_str(version)
+    }
+}
@@ -0,0 +1,53 @@
+use crate::Location;
+
+/// Describes a warning raised while lexing
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub message: String,
+    pub location: Location,
+}
+
+/// The kinds of warnings raised by the lexer
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    /// An operator followed by an argument was interpreted as an argument prefix (e.g. `foo *a`)
+    ArgumentPrefix,
+    /// An operator was interpreted as the start of the first argument (e.g. `foo -1`)
+    AmbiguousFirstArgument,
+    /// A character literal of a whitespace character (e.g. `? `)
+    InvalidCharacterSyntax,
+    /// A numbered match reference that is too big to refer to a group (e.g. `$9999999999`)
+    NthRefTooBig,
+    /// A magic comment that has no effect where it appears (e.g. `frozen_string_literal` after
+    /// the first token)
+    IgnoredMagicComment,
+    /// A magic comment with an invalid value (e.g. `# frozen_string_literal: maybe`)
+    InvalidMagicCommentValue,
+}
+
+impl WarningKind {
+    /// Returns the lowest warning level at which warnings of this kind are reported
+    pub fn level(self) -> WarningLevel {
+        match self {
+            WarningKind::ArgumentPrefix
+            | WarningKind::AmbiguousFirstArgument
+            | WarningKind::IgnoredMagicComment => WarningLevel::Verbose,
+            WarningKind::InvalidCharacterSyntax
+            | WarningKind::NthRefTooBig
+            | WarningKind::InvalidMagicCommentValue => WarningLevel::Medium,
+        }
+    }
+}
+
+/// The verbosity of the warnings reported by the lexer, matching Ruby's `-W` flag
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WarningLevel {
+    /// No warnings are reported (`-W0`)
+    Silent,
+    /// Important warnings are reported (`-W1`, Ruby's default)
+    #[default]
+    Medium,
+    /// All warnings are reported (`-W2` or `-w`)
+    Verbose,
+}